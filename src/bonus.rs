@@ -0,0 +1,259 @@
+// === ボーナスゲームモジュール ===
+// パチスロのBIG・REGボーナス：決まったシンボルが有効ラインに揃うとボーナス状態に入り、
+// 決まったゲーム数を消化するか払い出しが上限に達するまで、当選しやすい専用の抽選テーブルで遊ぶ
+// マシン定義に [[bonus]] がある場合のみ有効になる（内部抽選のあるマシンのみ）
+use std::fmt;
+
+use crate::lottery::Lottery;
+use crate::machine::{MachineDef, SymbolId};
+use crate::reel::LineWin;
+
+/// ボーナスの種類
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BonusKind {
+    /// ビッグボーナス（長く続く大きなボーナス）
+    Big,
+    /// レギュラーボーナス（短い小さなボーナス）
+    Reg,
+}
+
+impl fmt::Display for BonusKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BonusKind::Big => write!(f, "BIG BONUS"),
+            BonusKind::Reg => write!(f, "REG BONUS"),
+        }
+    }
+}
+
+/// ボーナスの定義（どの役で始まり、いつ終わるか）
+pub struct BonusDef {
+    pub kind: BonusKind,
+    pub symbol: SymbolId,          // 揃うとボーナスが始まるシンボル
+    pub count: usize,              // 揃う数
+    pub games: u32,                // ボーナスのゲーム数
+    pub payout_cap: Option<u64>,   // 払い出しの上限（1枚掛け換算、達した時点で終了）
+    lotteries: Vec<Lottery>,       // ボーナス中の抽選テーブル（設定共通の1つ / 設定1〜6の6つ）
+}
+
+impl BonusDef {
+    /// ボーナスの定義を作成
+    ///
+    /// # 引数
+    /// * `lotteries` - ボーナス中の抽選テーブル（設定共通なら1つ、設定ごとなら6つ）
+    pub fn new(
+        kind: BonusKind,
+        symbol: SymbolId,
+        count: usize,
+        games: u32,
+        payout_cap: Option<u64>,
+        lotteries: Vec<Lottery>,
+    ) -> Self {
+        Self {
+            kind,
+            symbol,
+            count,
+            games,
+            payout_cap,
+            lotteries,
+        }
+    }
+
+    /// 指定した設定でのボーナス中の抽選テーブル
+    ///
+    /// # 引数
+    /// * `setting` - 設定（1〜6）
+    pub fn lottery(&self, setting: usize) -> Option<&Lottery> {
+        match self.lotteries.len() {
+            1 => self.lotteries.first(),
+            _ => self.lotteries.get(setting.wrapping_sub(1)),
+        }
+    }
+
+    /// ボーナス中の抽選テーブル（設定の数だけある）
+    pub fn lotteries(&self) -> &[Lottery] {
+        &self.lotteries
+    }
+}
+
+/// 消化中のボーナスの進み具合
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BonusRun {
+    pub bonus: usize,  // MachineDef::bonuses の添字
+    pub games: u32,    // 消化したゲーム数
+    pub paid: u64,     // これまでの払い出し（1枚掛け換算）
+}
+
+/// ゲームの状態（通常時 / BIG中 / REG中）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameState {
+    Normal,
+    Big(BonusRun),
+    Reg(BonusRun),
+}
+
+/// 1ゲームの精算で起きた状態の切り替わり
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Transition {
+    /// ボーナスが始まった
+    Started(BonusKind),
+    /// ボーナスが終わった（消化ゲーム数と払い出し合計）
+    Ended { kind: BonusKind, games: u32, paid: u64 },
+}
+
+impl GameState {
+    /// 消化中のボーナス（通常時はNone）
+    pub fn run(&self) -> Option<&BonusRun> {
+        match self {
+            GameState::Normal => None,
+            GameState::Big(run) | GameState::Reg(run) => Some(run),
+        }
+    }
+
+    /// 消化中のボーナスの種類（通常時はNone）
+    pub fn kind(&self) -> Option<BonusKind> {
+        match self {
+            GameState::Normal => None,
+            GameState::Big(_) => Some(BonusKind::Big),
+            GameState::Reg(_) => Some(BonusKind::Reg),
+        }
+    }
+
+    /// 現在の状態で使う抽選テーブル（ボーナス中は専用のテーブル）
+    ///
+    /// # 引数
+    /// * `machine` - マシン定義
+    /// * `setting` - 設定（1〜6）
+    pub fn lottery<'a>(&self, machine: &'a MachineDef, setting: usize) -> Option<&'a Lottery> {
+        match self.run() {
+            Some(run) => machine.bonuses[run.bonus].lottery(setting),
+            None => machine.lottery(setting),
+        }
+    }
+
+    /// 1ゲームの当選結果で状態を進める
+    /// 通常時はボーナスの役が揃えばボーナスを開始し、
+    /// ボーナス中はゲーム数と払い出しを数えて、どちらかが上限に達したら通常時に戻す
+    ///
+    /// # 引数
+    /// * `machine` - マシン定義
+    /// * `wins` - そのゲームの当選ライン
+    /// * `coins_per_line` - そのゲームの1ラインあたりの掛け枚数（払い出しの1枚掛け換算に使う）
+    ///
+    /// # 戻り値
+    /// 状態が切り替わった場合はその内容
+    pub fn advance(&mut self, machine: &MachineDef, wins: &[LineWin], coins_per_line: u64) -> Option<Transition> {
+        match self {
+            GameState::Normal => {
+                let (index, bonus) = machine.bonuses.iter().enumerate().find(|(_, bonus)| {
+                    wins.iter().any(|win| win.symbol == bonus.symbol && win.count == bonus.count)
+                })?;
+                let run = BonusRun {
+                    bonus: index,
+                    games: 0,
+                    paid: 0,
+                };
+                *self = match bonus.kind {
+                    BonusKind::Big => GameState::Big(run),
+                    BonusKind::Reg => GameState::Reg(run),
+                };
+                Some(Transition::Started(bonus.kind))
+            }
+            GameState::Big(run) | GameState::Reg(run) => {
+                run.games += 1;
+                run.paid += wins.iter().map(|win| win.payout).sum::<u64>() / coins_per_line.max(1);
+                let bonus = &machine.bonuses[run.bonus];
+                let finished = run.games >= bonus.games || bonus.payout_cap.is_some_and(|cap| run.paid >= cap);
+                if !finished {
+                    return None;
+                }
+                let ended = Transition::Ended {
+                    kind: bonus.kind,
+                    games: run.games,
+                    paid: run.paid,
+                };
+                *self = GameState::Normal;
+                Some(ended)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    /// BIG（30ゲーム・上限600枚）とREG（12ゲーム・上限200枚）のあるパチスロ風マシン
+    fn pachislot() -> MachineDef {
+        MachineDef::from_toml(
+            include_str!("../machines/pachislot-3x3.toml"),
+            Path::new("machines/pachislot-3x3.toml"),
+        )
+        .unwrap()
+    }
+
+    /// 1本のラインで揃った当選（判定に使うのはシンボル・数・配当だけ）
+    fn win(machine: &MachineDef, glyph: &str, count: usize, payout: u64) -> LineWin {
+        LineWin {
+            line: Some(0),
+            entry: 0,
+            symbol: machine.symbols.iter().position(|symbol| symbol.glyph == glyph).unwrap(),
+            count,
+            payout,
+            multiplier: 1,
+            ways: 1,
+        }
+    }
+
+    #[test]
+    fn bonus_symbol_wins_start_their_bonus() {
+        let machine = pachislot();
+        let started = |bonus| BonusRun { bonus, games: 0, paid: 0 };
+        // (揃った当選, 期待する切り替わり, 期待する状態)
+        let cases = [
+            (win(&machine, "🔴", 3, 15), Some(Transition::Started(BonusKind::Big)), GameState::Big(started(0))),
+            (win(&machine, "⬛", 3, 15), Some(Transition::Started(BonusKind::Reg)), GameState::Reg(started(1))),
+            (win(&machine, "🔔", 3, 10), None,                                      GameState::Normal),
+            (win(&machine, "🔴", 2, 0),  None,                                      GameState::Normal),  // 数が足りない
+        ];
+        for (win, transition, state) in cases {
+            let mut game = GameState::Normal;
+            assert_eq!(game.advance(&machine, &[win], 1), transition);
+            assert_eq!(game, state);
+        }
+        // 外れでは通常時のまま
+        let mut game = GameState::Normal;
+        assert_eq!(game.advance(&machine, &[], 1), None);
+        assert_eq!(game, GameState::Normal);
+    }
+
+    #[test]
+    fn a_bonus_ends_after_its_games() {
+        let machine = pachislot();
+        // REG BONUS は12ゲームで終わる（1ゲーム10枚なら上限200枚には届かない）
+        let mut game = GameState::Reg(BonusRun { bonus: 1, games: 0, paid: 0 });
+        for games in 1..12 {
+            assert_eq!(game.advance(&machine, &[win(&machine, "🔔", 3, 10)], 1), None);
+            assert_eq!(game.run().map(|run| (run.games, run.paid)), Some((games, games as u64 * 10)));
+        }
+        let ended = game.advance(&machine, &[], 1);
+        assert_eq!(ended, Some(Transition::Ended { kind: BonusKind::Reg, games: 12, paid: 110 }));
+        assert_eq!(game, GameState::Normal);
+    }
+
+    #[test]
+    fn a_bonus_ends_when_its_payout_reaches_the_cap() {
+        let machine = pachislot();
+        // BIG BONUS を3枚掛けで消化する：1ゲーム150クレジットは1枚掛け換算で50枚、12ゲームで上限600枚
+        let mut game = GameState::Big(BonusRun { bonus: 0, games: 0, paid: 0 });
+        let bell = win(&machine, "🔔", 3, 150);
+        for _ in 1..12 {
+            assert_eq!(game.advance(&machine, std::slice::from_ref(&bell), 3), None);
+        }
+        assert_eq!(game.run().map(|run| run.paid), Some(550));
+        let ended = game.advance(&machine, &[bell], 3);
+        assert_eq!(ended, Some(Transition::Ended { kind: BonusKind::Big, games: 12, paid: 600 }));
+        assert_eq!(game, GameState::Normal);
+    }
+}
@@ -0,0 +1,268 @@
+// === カスケード（連鎖）モジュール ===
+// 当選したシンボルを盤面から消し、残ったシンボルを下に詰めて、空いたマスにリール配列の続きを上から落とす
+// 落とした後の盤面をもう一度判定し、当選がなくなるまで連鎖の倍率を上げながら繰り返す
+// 落ちてくるシンボルは停止位置から決まる（乱数を使わない）ため、精算時にまとめて計算してもリプレイと一致する
+use crate::machine::{MachineDef, SymbolId};
+use crate::paytable::PayRule;
+use crate::reel::{evaluate, visible_symbols, LineWin};
+
+/// 1ゲームの連鎖の上限（同じシンボルばかりのリール配列で連鎖が終わらなくなるのを防ぐ）
+const MAX_CASCADES: usize = 50;
+
+/// カスケードの定義
+pub struct CascadeDef {
+    pub multipliers: Vec<u64>,  // 判定ごとの配当倍率（停止時の盤面から順に、足りない分は最後の倍率を使う）
+}
+
+impl CascadeDef {
+    /// 何回目の判定（0が停止時の盤面）の配当倍率か
+    pub fn multiplier(&self, step: usize) -> u64 {
+        self.multipliers[step.min(self.multipliers.len() - 1)]
+    }
+
+    /// 連鎖の倍率の並び（「×1 → ×2 → ×3…」、最後の倍率が以降も続く）
+    pub fn label(&self) -> String {
+        let multipliers: Vec<String> = self.multipliers.iter().map(|multiplier| format!("×{}", multiplier)).collect();
+        format!("{}…", multipliers.join(" → "))
+    }
+}
+
+/// 連鎖の1段階分（1回の判定）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CascadeStep {
+    pub window: Vec<Vec<SymbolId>>,  // この段階の盤面（[リール][段]）
+    pub wins: Vec<LineWin>,          // この盤面の当選（配当は連鎖の倍率を掛けたもの）
+    pub removed: Vec<Vec<bool>>,     // 当選で消えるマス（[リール][段]）
+    pub multiplier: u64,             // この段階の連鎖の倍率
+}
+
+/// 停止位置から1ゲーム分の連鎖をすべて計算する
+/// 最初の段階は停止時の盤面で、当選のない盤面（wins が空）か連鎖の上限で終わる
+/// 消えたマスには、リール配列で停止位置より手前（上から落ちてくる側）のシンボルを順に補充する
+/// カスケードのないマシンでは停止時の盤面の1段階だけを返す
+///
+/// # 引数
+/// * `machine` - マシン定義（リール配列・判定方式・配当表・カスケードの定義）
+/// * `stops` - 各リールの停止位置
+/// * `paylines` - 判定する有効ライン（無効なラインは渡さない）
+/// * `coins_per_line` - 1ラインあたりの掛け枚数
+pub fn run(machine: &MachineDef, stops: &[usize], paylines: &[Vec<usize>], coins_per_line: u64) -> Vec<CascadeStep> {
+    let mut window: Vec<Vec<SymbolId>> = machine
+        .reels
+        .iter()
+        .zip(stops)
+        .map(|(strip, &stop)| visible_symbols(strip, stop, machine.rows))
+        .collect();
+    let mut dropped = vec![0usize; machine.reels.len()];  // リールごとに補充したシンボルの数
+    let mut steps: Vec<CascadeStep> = Vec::new();
+    loop {
+        let multiplier = machine.cascade.as_ref().map_or(1, |cascade| cascade.multiplier(steps.len()));
+        let mut wins = evaluate(machine, &window, paylines, coins_per_line);
+        for win in &mut wins {
+            win.payout *= multiplier;
+        }
+        let removed = winning_cells(machine, &window, paylines, &wins);
+        let last = wins.is_empty() || machine.cascade.is_none() || steps.len() >= MAX_CASCADES;
+        steps.push(CascadeStep {
+            window: window.clone(),
+            wins,
+            removed,
+            multiplier,
+        });
+        if last {
+            return steps;
+        }
+
+        // 消えたマスを詰め、空いた分をリール配列の手前側から上に補充する
+        let removed = &steps[steps.len() - 1].removed;
+        for (reel, column) in window.iter_mut().enumerate() {
+            let strip = &machine.reels[reel];
+            let kept: Vec<SymbolId> = column
+                .iter()
+                .zip(&removed[reel])
+                .filter(|(_, &removed)| !removed)
+                .map(|(&symbol, _)| symbol)
+                .collect();
+            let refill = machine.rows - kept.len();
+            let mut next: Vec<SymbolId> = (0..refill)
+                .map(|i| {
+                    // 上段に来るのは最も手前のシンボル（i=0 が補充分の一番上）
+                    let back = (dropped[reel] + refill - i) % strip.len();
+                    strip[(stops[reel] + strip.len() - back) % strip.len()]
+                })
+                .collect();
+            next.extend(kept);
+            *column = next;
+            dropped[reel] += refill;
+        }
+    }
+}
+
+/// 連鎖を含めた1ゲームの当選をすべて返す（シミュレーション・パーシート用）
+/// カスケードのないマシン・停止時の盤面がハズレの場合は evaluate と同じ
+///
+/// # 引数
+/// * `machine` - マシン定義
+/// * `window` - 停止時の盤面（[リール][段]）
+/// * `stops` - 各リールの停止位置（window と同じ位置）
+/// * `paylines` - 判定する有効ライン（無効なラインは渡さない）
+/// * `coins_per_line` - 1ラインあたりの掛け枚数
+pub fn evaluate_all(
+    machine: &MachineDef,
+    window: &[Vec<SymbolId>],
+    stops: &[usize],
+    paylines: &[Vec<usize>],
+    coins_per_line: u64,
+) -> Vec<LineWin> {
+    let wins = evaluate(machine, window, paylines, coins_per_line);
+    if machine.cascade.is_none() || wins.is_empty() {
+        return wins;
+    }
+    run(machine, stops, paylines, coins_per_line)
+        .into_iter()
+        .flat_map(|step| step.wins)
+        .collect()
+}
+
+/// 当選に使われたマスを求める
+/// ラインの当選は揃った範囲（左からなら先頭の count 個、どこでもならライン上の対象）、
+/// ウェイの当選は先頭の count 個のリールで対象になったすべての段
+/// スキャッタは消さない（配当は停止時の盤面でのみ数える）
+fn winning_cells(machine: &MachineDef, window: &[Vec<SymbolId>], paylines: &[Vec<usize>], wins: &[LineWin]) -> Vec<Vec<bool>> {
+    let mut removed = vec![vec![false; machine.rows]; window.len()];
+    for win in wins {
+        let entry = &machine.paytable.entries()[win.entry];
+        let matches = |symbol: SymbolId| machine.paytable.covers(entry, symbol) || machine.symbols[symbol].wild;
+        match win.line {
+            Some(line) => {
+                let span = match entry.rule {
+                    PayRule::Left => entry.count,
+                    PayRule::Anywhere => paylines[line].len(),
+                };
+                for (reel, &row) in paylines[line].iter().enumerate().take(span) {
+                    if matches(window[reel][row]) {
+                        removed[reel][row] = true;
+                    }
+                }
+            }
+            None => {
+                for (reel, rows) in window.iter().enumerate().take(entry.count) {
+                    for (row, &symbol) in rows.iter().enumerate() {
+                        if matches(symbol) {
+                            removed[reel][row] = true;
+                        }
+                    }
+                }
+            }
+        }
+    }
+    removed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    /// 3リール×2段・下段の1ラインで、停止位置1から手前に向かって🍒→🔔→👑→👑と連鎖するマシン
+    /// 停止位置1の盤面は上段が配列の1番目・下段が2番目で、消えると0番目・5番目・4番目…の順に落ちてくる
+    fn machine() -> MachineDef {
+        let source = r#"
+            name = "連鎖のテスト"
+            rows = 2
+            line_options = [1]
+            reels = [
+                ["👑", "🔔", "🍒", "🍋", "⬜", "👑"],
+                ["👑", "🔔", "🍒", "🍋", "⬜", "👑"],
+                ["👑", "🔔", "🍒", "🍋", "🍋", "👑"],
+            ]
+            paylines = [[1, 1, 1]]
+
+            [timing]
+            spin_step_ms = 35
+
+            [cascade]
+            multipliers = [1, 2, 3]
+
+            [[symbols]]
+            glyph = "👑"
+            name = "クラウン"
+
+            [[symbols]]
+            glyph = "🔔"
+            name = "ベル"
+
+            [[symbols]]
+            glyph = "🍒"
+            name = "チェリー"
+
+            [[symbols]]
+            glyph = "🍋"
+            name = "レモン"
+
+            [[symbols]]
+            glyph = "⬜"
+            name = "ブランク"
+
+            [[paytable]]
+            symbol = "👑"
+            count = 3
+            multiplier = 10
+
+            [[paytable]]
+            symbol = "🔔"
+            count = 3
+            multiplier = 5
+
+            [[paytable]]
+            symbol = "🍒"
+            count = 3
+            multiplier = 2
+        "#;
+        MachineDef::from_toml(source, Path::new("cascade.toml")).unwrap()
+    }
+
+    /// リールごとの段（上段, 下段）を絵文字で並べた盤面
+    fn window(machine: &MachineDef, reels: [[&str; 2]; 3]) -> Vec<Vec<SymbolId>> {
+        let id = |glyph: &str| machine.symbols.iter().position(|symbol| symbol.glyph == glyph).unwrap();
+        reels.iter().map(|rows| rows.iter().map(|&glyph| id(glyph)).collect()).collect()
+    }
+
+    #[test]
+    fn cascades_refill_from_the_strip_and_raise_the_multiplier() {
+        let machine = machine();
+        let steps = run(&machine, &[1, 1, 1], machine.paylines.active(1), 1);
+
+        // 下段が消えるたびに上段が下に詰まり、上段には配列の手前のシンボルが落ちてくる
+        let expected_windows = [
+            window(&machine, [["🔔", "🍒"], ["🔔", "🍒"], ["🔔", "🍒"]]),
+            window(&machine, [["👑", "🔔"], ["👑", "🔔"], ["👑", "🔔"]]),
+            window(&machine, [["👑", "👑"], ["👑", "👑"], ["👑", "👑"]]),
+            window(&machine, [["⬜", "👑"], ["⬜", "👑"], ["🍋", "👑"]]),
+            window(&machine, [["🍋", "⬜"], ["🍋", "⬜"], ["🍋", "🍋"]]),
+        ];
+        let windows: Vec<Vec<Vec<SymbolId>>> = steps.iter().map(|step| step.window.clone()).collect();
+        assert_eq!(windows, expected_windows);
+
+        // 倍率は ×1 → ×2 → ×3 で、定義の最後の倍率が以降も続く
+        let multipliers: Vec<u64> = steps.iter().map(|step| step.multiplier).collect();
+        assert_eq!(multipliers, vec![1, 2, 3, 3, 3]);
+        // 🍒 2 × 1、🔔 5 × 2、👑 10 × 3、👑 10 × 3、最後の盤面はハズレ
+        let payouts: Vec<Vec<u64>> = steps.iter().map(|step| step.wins.iter().map(|win| win.payout).collect()).collect();
+        assert_eq!(payouts, vec![vec![2], vec![10], vec![30], vec![30], vec![]]);
+
+        // 消えるのはライン上の下段だけ
+        for step in &steps[..4] {
+            assert_eq!(step.removed, vec![vec![false, true]; 3]);
+        }
+        assert_eq!(steps[4].removed, vec![vec![false, false]; 3]);
+
+        // まとめて判定しても連鎖の当選がすべて含まれる
+        let stopped = &steps[0].window;
+        let total: u64 = evaluate_all(&machine, stopped, &[1, 1, 1], machine.paylines.active(1), 1)
+            .iter()
+            .map(|win| win.payout)
+            .sum();
+        assert_eq!(total, 72);
+    }
+}
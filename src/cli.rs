@@ -0,0 +1,184 @@
+// === コマンドライン引数の解析モジュール ===
+// `gh slot` の後に続くサブコマンドとオプションを解釈する
+use std::path::PathBuf;
+
+use gh_slot::parsheet::SheetFormat;
+use gh_slot::setting::SETTINGS;
+
+// シミュレーションの既定の回転数
+pub const DEFAULT_SPINS: u64 = 1_000_000;
+
+// 使い方の説明（--help や引数エラー時に表示）
+pub const USAGE: &str = "\
+使い方:
+  gh slot [--machine <ファイル>] [--seed <シード>] [--log <ファイル>] [--setting <1-6>]
+      ターミナルでスロットを遊ぶ（操作はセッションログに記録される）
+      設定のあるマシンで --setting を省略すると設定は隠して抽選され、終了時に公開される
+  gh slot simulate [--machine <ファイル>] [--spins <回数>] [--lines <ライン数>] [--seed <シード>] [--setting <1-6>]
+      画面を使わずに大量の回転を行い、RTP・当選頻度などを集計する
+  gh slot replay <ログファイル>
+      記録したセッションを画面なしで再生し、停止位置と配当が記録と一致するか確認する
+  gh slot par-sheet [--machine <ファイル>] [--lines <ライン数>] [--format markdown|csv] [--output <ファイル>]
+      全停止位置の組み合わせを総当たりし、理論値表（パーシート）を出力する";
+
+/// 実行するコマンド
+#[derive(Debug, PartialEq, Eq)]
+pub enum Command {
+    /// ターミナルで遊ぶ（通常起動）
+    Play {
+        machine: Option<PathBuf>,
+        seed: Option<u64>,     // 未指定ならOSの乱数で決める
+        log: Option<PathBuf>,  // 未指定なら一時ディレクトリの gh-slot-session-<pid>-<シード>.log
+        setting: Option<usize>, // 未指定なら隠して抽選（設定のあるマシンのみ）
+    },
+    /// ヘッドレスのシミュレーション
+    Simulate {
+        machine: Option<PathBuf>,
+        spins: u64,
+        lines: Option<usize>,  // 未指定ならマシンの最大ライン数
+        seed: Option<u64>,     // 未指定ならOSの乱数で決める
+        setting: Option<usize>, // 未指定なら設定1（設定のあるマシンのみ）
+    },
+    /// セッションログの再生と照合
+    Replay { log: PathBuf },
+    /// 全組み合わせからのパーシート生成
+    ParSheet {
+        machine: Option<PathBuf>,
+        lines: Option<usize>,        // 未指定ならマシンの最大ライン数
+        format: SheetFormat,
+        output: Option<PathBuf>,     // 未指定なら標準出力
+    },
+    /// 使い方を表示して終了
+    Help,
+}
+
+/// コマンドライン引数（プログラム名を除く）を解析する
+///
+/// # 戻り値
+/// 解析結果のコマンド、または利用者向けのエラーメッセージ
+pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Command, String> {
+    let mut args = args.into_iter().peekable();
+
+    // 先頭が「-」で始まらなければサブコマンド
+    let subcommand = match args.peek() {
+        Some(arg) if !arg.starts_with('-') => args.next(),
+        _ => None,
+    };
+
+    let mut machine: Option<PathBuf> = None;
+    let mut spins: Option<u64> = None;
+    let mut lines: Option<usize> = None;
+    let mut format: Option<SheetFormat> = None;
+    let mut output: Option<PathBuf> = None;
+    let mut seed: Option<u64> = None;
+    let mut log: Option<PathBuf> = None;
+    let mut replay_log: Option<PathBuf> = None;
+    let mut setting: Option<usize> = None;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--machine" => machine = Some(PathBuf::from(value(&mut args, &arg)?)),
+            "--spins" => spins = Some(number(&value(&mut args, &arg)?, &arg)?),
+            "--lines" => lines = Some(number(&value(&mut args, &arg)?, &arg)?),
+            "--format" => format = Some(sheet_format(&value(&mut args, &arg)?)?),
+            "--output" => output = Some(PathBuf::from(value(&mut args, &arg)?)),
+            "--seed" => seed = Some(seed_value(&value(&mut args, &arg)?)?),
+            "--log" => log = Some(PathBuf::from(value(&mut args, &arg)?)),
+            "--setting" => setting = Some(setting_value(&value(&mut args, &arg)?)?),
+            "-h" | "--help" => return Ok(Command::Help),
+            // replay の後ろの引数は再生するログファイル
+            _ if subcommand.as_deref() == Some("replay") && replay_log.is_none() && !arg.starts_with('-') => {
+                replay_log = Some(PathBuf::from(arg));
+            }
+            _ => return Err(format!("不明な引数です: {}", arg)),
+        }
+    }
+
+    // サブコマンドごとに使えるオプションを確認
+    let subcommand = subcommand.as_deref();
+    if spins.is_some() && subcommand != Some("simulate") {
+        return Err("--spins は simulate でのみ使えます".to_string());
+    }
+    if lines.is_some() && !matches!(subcommand, Some("simulate" | "par-sheet")) {
+        return Err("--lines は simulate / par-sheet でのみ使えます".to_string());
+    }
+    if (format.is_some() || output.is_some()) && subcommand != Some("par-sheet") {
+        return Err("--format / --output は par-sheet でのみ使えます".to_string());
+    }
+    if seed.is_some() && !matches!(subcommand, None | Some("simulate")) {
+        return Err("--seed はゲーム本体と simulate でのみ使えます".to_string());
+    }
+    if setting.is_some() && !matches!(subcommand, None | Some("simulate")) {
+        return Err("--setting はゲーム本体と simulate でのみ使えます".to_string());
+    }
+    if log.is_some() && subcommand.is_some() {
+        return Err("--log はゲーム本体でのみ使えます".to_string());
+    }
+    if machine.is_some() && subcommand == Some("replay") {
+        return Err("replay ではマシン定義をセッションログから読み込みます".to_string());
+    }
+
+    match subcommand {
+        None => Ok(Command::Play {
+            machine,
+            seed,
+            log,
+            setting,
+        }),
+        Some("simulate") => Ok(Command::Simulate {
+            machine,
+            spins: spins.unwrap_or(DEFAULT_SPINS),
+            lines,
+            seed,
+            setting,
+        }),
+        Some("replay") => match replay_log {
+            Some(log) => Ok(Command::Replay { log }),
+            None => Err("replay には再生するログファイルを指定してください".to_string()),
+        },
+        Some("par-sheet") => Ok(Command::ParSheet {
+            machine,
+            lines,
+            format: format.unwrap_or(SheetFormat::Markdown),
+            output,
+        }),
+        Some("help") => Ok(Command::Help),
+        Some(other) => Err(format!("不明なサブコマンドです: {}", other)),
+    }
+}
+
+/// オプションの値を取り出す（値がなければエラー）
+fn value(args: &mut impl Iterator<Item = String>, option: &str) -> Result<String, String> {
+    args.next()
+        .ok_or_else(|| format!("{} には値が必要です", option))
+}
+
+/// パーシートの出力形式を解釈する
+fn sheet_format(text: &str) -> Result<SheetFormat, String> {
+    match text {
+        "markdown" | "md" => Ok(SheetFormat::Markdown),
+        "csv" => Ok(SheetFormat::Csv),
+        _ => Err(format!("--format には markdown か csv を指定してください: {}", text)),
+    }
+}
+
+/// 乱数シードを解釈する（0も指定できる）
+fn seed_value(text: &str) -> Result<u64, String> {
+    text.parse()
+        .map_err(|_| format!("--seed には0以上の整数を指定してください: {}", text))
+}
+
+/// 設定（1〜6）を解釈する
+fn setting_value(text: &str) -> Result<usize, String> {
+    match text.parse() {
+        Ok(setting) if (1..=SETTINGS).contains(&setting) => Ok(setting),
+        _ => Err(format!("--setting には1〜{}を指定してください: {}", SETTINGS, text)),
+    }
+}
+
+/// オプションの値を正の整数として解釈する
+fn number<T: std::str::FromStr + PartialEq + Default>(text: &str, option: &str) -> Result<T, String> {
+    match text.parse::<T>() {
+        Ok(n) if n != T::default() => Ok(n),
+        _ => Err(format!("{} には1以上の整数を指定してください: {}", option, text)),
+    }
+}
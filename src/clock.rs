@@ -0,0 +1,71 @@
+// === 時計モジュール ===
+// ゲームループ・リールが使う「今の時刻」と「待つ」処理をまとめる
+// 本番は実時間の時計、テストでは手で進める時計を差し込み、待ち時間なしで回転速度や停止のタイミングを確かめられる
+// 時刻はすべてセッション開始からのミリ秒で表す（セッションログ・リール位置と同じ時間軸）
+use std::time::{Duration, Instant};
+
+/// ゲームループ・リールが使う時計
+pub trait Clock {
+    /// 現在の時刻（セッション開始からのミリ秒）
+    fn now_ms(&self) -> u64;
+
+    /// 指定した時刻まで待つ（過ぎていればすぐに戻る）
+    fn sleep_until(&self, at_ms: u64);
+}
+
+/// 実時間の時計（ライブプレイ用）
+pub struct SystemClock {
+    epoch: Instant,  // セッション開始時刻
+}
+
+impl SystemClock {
+    /// 今をセッション開始時刻とする時計を作る
+    pub fn start() -> Self {
+        Self { epoch: Instant::now() }
+    }
+}
+
+impl Clock for SystemClock {
+    fn now_ms(&self) -> u64 {
+        self.epoch.elapsed().as_millis() as u64
+    }
+
+    fn sleep_until(&self, at_ms: u64) {
+        if let Some(remaining) = Duration::from_millis(at_ms).checked_sub(self.epoch.elapsed()) {
+            std::thread::sleep(remaining);
+        }
+    }
+}
+
+/// 手で進める時計（テスト・画面を持たないフロントエンド用）
+/// 待つ処理は実際には待たず、時刻をその時点まで進めて戻る
+#[derive(Default)]
+pub struct ManualClock {
+    now_ms: std::cell::Cell<u64>,  // 現在の時刻
+}
+
+impl ManualClock {
+    /// 時刻0から始まる時計を作る
+    pub fn new() -> Self {
+        Self {
+            now_ms: std::cell::Cell::new(0),
+        }
+    }
+
+    /// 時刻を指定したミリ秒だけ進める
+    pub fn advance(&self, ms: u64) {
+        self.now_ms.set(self.now_ms.get() + ms);
+    }
+}
+
+impl Clock for ManualClock {
+    fn now_ms(&self) -> u64 {
+        self.now_ms.get()
+    }
+
+    fn sleep_until(&self, at_ms: u64) {
+        if at_ms > self.now_ms.get() {
+            self.now_ms.set(at_ms);
+        }
+    }
+}
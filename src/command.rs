@@ -0,0 +1,73 @@
+// === 操作コマンドモジュール ===
+// キー入力から作られるゲーム操作と、セッションログに書き出すときの文字列表現
+use std::fmt;
+use std::str::FromStr;
+
+use crate::gamble::Guess;
+
+/// ゲーム操作（キー入力1回分）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReelCommand {
+    StartAll,          // 全リール回転開始
+    Stop(usize),       // 指定リール停止（0:左端）
+    StopNext,          // 回転中で一番左のリールを停止
+    Hold(usize),       // 指定リールのホールドを切り替え（ホールドの権利があるときのみ）
+    NudgeUp(usize),    // 指定リールを1コマ上へ動かす（ナッジが残っているときのみ）
+    NudgeDown(usize),  // 指定リールを1コマ下へ動かす（ナッジが残っているときのみ）
+    Gamble(Guess),     // ダブルアップの予想（当たりのあとのみ）
+    Collect,           // ダブルアップをやめて受け取る
+    ChangeBet,         // 掛け枚数切替
+    ChangeLines,       // 有効ライン数切替
+    ToggleHelp,        // 配当表の表示/非表示
+    Exit,              // ゲーム終了
+}
+
+// セッションログ用の表現（例: "start", "stop 2"）
+impl fmt::Display for ReelCommand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReelCommand::StartAll => write!(f, "start"),
+            ReelCommand::Stop(index) => write!(f, "stop {}", index),
+            ReelCommand::StopNext => write!(f, "stop-next"),
+            ReelCommand::Hold(index) => write!(f, "hold {}", index),
+            ReelCommand::NudgeUp(index) => write!(f, "nudge-up {}", index),
+            ReelCommand::NudgeDown(index) => write!(f, "nudge-down {}", index),
+            ReelCommand::Gamble(guess) => write!(f, "gamble {}", guess.name()),
+            ReelCommand::Collect => write!(f, "collect"),
+            ReelCommand::ChangeBet => write!(f, "bet"),
+            ReelCommand::ChangeLines => write!(f, "lines"),
+            ReelCommand::ToggleHelp => write!(f, "help"),
+            ReelCommand::Exit => write!(f, "exit"),
+        }
+    }
+}
+
+impl FromStr for ReelCommand {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let mut words = text.split_whitespace();
+        let reel = |index: &str| index.parse().map_err(|_| format!("リール番号が不正です: {}", index));
+        let command = match (words.next(), words.next()) {
+            (Some("start"), None) => ReelCommand::StartAll,
+            (Some("stop"), Some(index)) => ReelCommand::Stop(reel(index)?),
+            (Some("stop-next"), None) => ReelCommand::StopNext,
+            (Some("hold"), Some(index)) => ReelCommand::Hold(reel(index)?),
+            (Some("nudge-up"), Some(index)) => ReelCommand::NudgeUp(reel(index)?),
+            (Some("nudge-down"), Some(index)) => ReelCommand::NudgeDown(reel(index)?),
+            (Some("gamble"), Some(guess)) => ReelCommand::Gamble(
+                Guess::from_name(guess).ok_or_else(|| format!("ダブルアップの予想が不正です: {}", guess))?,
+            ),
+            (Some("collect"), None) => ReelCommand::Collect,
+            (Some("bet"), None) => ReelCommand::ChangeBet,
+            (Some("lines"), None) => ReelCommand::ChangeLines,
+            (Some("help"), None) => ReelCommand::ToggleHelp,
+            (Some("exit"), None) => ReelCommand::Exit,
+            _ => return Err(format!("不明な操作です: {}", text)),
+        };
+        if words.next().is_some() {
+            return Err(format!("不明な操作です: {}", text));
+        }
+        Ok(command)
+    }
+}
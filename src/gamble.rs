@@ -0,0 +1,215 @@
+// === ダブルアップ（ギャンブル）モジュール ===
+// 当たりのあと、獲得したクレジットを賭けてトランプの色（赤/黒）か数の大小（ハイ/ロー）を当てる
+// 当たれば賭け金が倍になり、外れると没収される。決められた回数まで続けるか、途中で受け取る
+// カードはセッションの乱数で引くため、リプレイでも同じカードが出る
+use rand::Rng;
+use std::fmt;
+
+/// ダブルアップの定義
+pub struct GambleDef {
+    pub ladder: u32,  // 続けて倍にできる回数（到達すると自動で受け取る）
+}
+
+/// カードのマーク
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Suit {
+    Spade,
+    Heart,
+    Diamond,
+    Club,
+}
+
+/// 引いたカード
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Card {
+    pub suit: Suit,
+    pub rank: u8,  // 1（A）〜13（K）
+}
+
+impl Card {
+    /// 52枚のトランプから1枚引く（毎回すべてのカードから引く）
+    pub fn draw(rng: &mut impl Rng) -> Self {
+        let index = rng.gen_range(0..52u8);
+        let suit = [Suit::Spade, Suit::Heart, Suit::Diamond, Suit::Club][(index / 13) as usize];
+        Self { suit, rank: index % 13 + 1 }
+    }
+
+    /// 赤のカード（♥・♦）かどうか
+    pub fn is_red(&self) -> bool {
+        matches!(self.suit, Suit::Heart | Suit::Diamond)
+    }
+}
+
+// 画面表示用の表現（例: "♥Q"）
+impl fmt::Display for Card {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let suit = match self.suit {
+            Suit::Spade => "♠",
+            Suit::Heart => "♥",
+            Suit::Diamond => "♦",
+            Suit::Club => "♣",
+        };
+        let rank = match self.rank {
+            1 => "A".to_string(),
+            11 => "J".to_string(),
+            12 => "Q".to_string(),
+            13 => "K".to_string(),
+            rank => rank.to_string(),
+        };
+        write!(f, "{}{}", suit, rank)
+    }
+}
+
+/// プレイヤーの予想
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Guess {
+    Red,    // 赤（♥・♦）
+    Black,  // 黒（♠・♣）
+    High,   // ハイ（8〜K、7は外れ）
+    Low,    // ロー（A〜6、7は外れ）
+}
+
+impl Guess {
+    /// 引いたカードで予想が当たったか
+    pub fn wins(self, card: Card) -> bool {
+        match self {
+            Guess::Red => card.is_red(),
+            Guess::Black => !card.is_red(),
+            Guess::High => card.rank > 7,
+            Guess::Low => card.rank < 7,
+        }
+    }
+
+    /// 画面表示用の名前
+    pub fn label(self) -> &'static str {
+        match self {
+            Guess::Red => "赤",
+            Guess::Black => "黒",
+            Guess::High => "ハイ",
+            Guess::Low => "ロー",
+        }
+    }
+
+    /// セッションログ用の名前
+    pub fn name(self) -> &'static str {
+        match self {
+            Guess::Red => "red",
+            Guess::Black => "black",
+            Guess::High => "high",
+            Guess::Low => "low",
+        }
+    }
+
+    /// セッションログの名前から予想を読み取る
+    pub fn from_name(name: &str) -> Option<Self> {
+        [Guess::Red, Guess::Black, Guess::High, Guess::Low]
+            .into_iter()
+            .find(|guess| guess.name() == name)
+    }
+}
+
+/// 1回分のダブルアップの結果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GambleDraw {
+    pub guess: Guess,  // 予想
+    pub card: Card,    // 引いたカード
+    pub stake: u64,    // 賭けたクレジット
+    pub result: u64,   // 結果のクレジット（当たれば倍、外れれば0）
+}
+
+/// 当たりのあとのダブルアップ（次のゲームが始まるまで結果を表示に残す）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Gamble {
+    pub stake: u64,               // 現在の賭け金（受け取れるクレジット）
+    pub round: u32,               // 倍にした回数
+    pub open: bool,               // まだ続けられるか（外れ・受け取り・上限到達で終わる）
+    pub last: Option<GambleDraw>, // 直前の結果
+}
+
+impl Gamble {
+    /// 獲得したクレジットでダブルアップを始める
+    pub fn new(stake: u64) -> Self {
+        Self {
+            stake,
+            round: 0,
+            open: true,
+            last: None,
+        }
+    }
+
+    /// カードを引いて予想の結果を反映する
+    /// 外れるか上限の回数に達すると終わる
+    ///
+    /// # 戻り値
+    /// 今回の結果
+    pub fn play(&mut self, def: &GambleDef, guess: Guess, rng: &mut impl Rng) -> GambleDraw {
+        let card = Card::draw(rng);
+        let won = guess.wins(card);
+        let draw = GambleDraw {
+            guess,
+            card,
+            stake: self.stake,
+            result: if won { self.stake * 2 } else { 0 },
+        };
+        self.stake = draw.result;
+        self.last = Some(draw);
+        if won {
+            self.round += 1;
+        }
+        self.open = won && self.round < def.ladder;
+        draw
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::SessionRng;
+
+    /// 同じシードの乱数で次に引かれるカードを先に見て、当たる（外れる）予想を選ぶ
+    fn guess_to(peek: &mut SessionRng, win: bool) -> Guess {
+        if Card::draw(peek).is_red() == win {
+            Guess::Red
+        } else {
+            Guess::Black
+        }
+    }
+
+    #[test]
+    fn wins_double_the_stake_until_the_ladder() {
+        let def = GambleDef { ladder: 3 };
+        let (mut rng, mut peek) = (SessionRng::seeded(7), SessionRng::seeded(7));
+        let mut gamble = Gamble::new(10);
+        // (倍にする前の賭け金, 倍にした後の賭け金, まだ続けられるか)
+        for (stake, result, open) in [(10, 20, true), (20, 40, true), (40, 80, false)] {
+            let guess = guess_to(&mut peek, true);
+            let draw = gamble.play(&def, guess, &mut rng);
+            assert_eq!((draw.guess, draw.stake, draw.result), (guess, stake, result));
+            assert!(guess.wins(draw.card));
+            assert_eq!((gamble.stake, gamble.open), (result, open));
+            assert_eq!(gamble.last, Some(draw));
+        }
+        // 上限に達したら倍にした回数は ladder で止まる
+        assert_eq!(gamble.round, 3);
+    }
+
+    #[test]
+    fn a_miss_forfeits_the_stake() {
+        let def = GambleDef { ladder: 3 };
+        let (mut rng, mut peek) = (SessionRng::seeded(11), SessionRng::seeded(11));
+        let mut gamble = Gamble::new(10);
+        gamble.play(&def, guess_to(&mut peek, true), &mut rng);
+        let draw = gamble.play(&def, guess_to(&mut peek, false), &mut rng);
+        assert_eq!((draw.stake, draw.result), (20, 0));
+        assert_eq!((gamble.stake, gamble.round, gamble.open), (0, 1, false));
+    }
+
+    #[test]
+    fn high_and_low_both_lose_on_a_seven() {
+        let seven = Card { suit: Suit::Spade, rank: 7 };
+        assert!(!Guess::High.wins(seven));
+        assert!(!Guess::Low.wins(seven));
+        assert!(Guess::High.wins(Card { suit: Suit::Club, rank: 8 }));
+        assert!(Guess::Low.wins(Card { suit: Suit::Heart, rank: 6 }));
+    }
+}
@@ -0,0 +1,1078 @@
+// === ゲーム進行モジュール ===
+// ベット・リールの回転と停止・精算・ボーナスやフリースピンなど、1台のスロットマシンの進行を管理する
+// 画面には依存しない（描画はフロントエンドが読み取り用のメソッドから状態を読んで行う）
+// 操作は apply で時刻とともに受け取るため、同じ操作列を同じ時刻で流せば結果も同じになる（リプレイ）
+use std::time::Duration;
+
+use rand::Rng;
+
+use crate::bonus::{BonusKind, GameState, Transition};
+use crate::cascade::{self, CascadeStep};
+use crate::command::ReelCommand;
+use crate::gamble::{Gamble, Guess};
+use crate::hold;
+use crate::jackpot::{JackpotPool, PoolState};
+use crate::lottery::Outcome;
+use crate::machine::{MachineDef, SymbolId};
+use crate::reel::{evaluate, Frame, LineWin, Reel};
+use crate::rng::SessionRng;
+use crate::scatter::{self, FreeSpinEvent, FreeSpins, ScatterWin};
+use crate::session::SpinResult;
+use crate::setting::{SettingStats, SETTINGS};
+use crate::wallet::{Wallet, INITIAL_CREDITS};
+
+// カスケードの1コマ（消える・落ちる）を見せる時間
+const CASCADE_FRAME: Duration = Duration::from_millis(450);
+// ジャックポットの積立額を読み直す間隔（他のセッションの積立を画面に反映する）
+const JACKPOT_POLL: Duration = Duration::from_millis(500);
+
+/// スロットマシン全体を管理する構造体
+/// リールと前回の回転状態、クレジット情報、マシン定義を保持
+/// リール数・表示段数はマシン定義に従う（3×3、3×1、5×3、5×4など）
+pub struct SlotMachine {
+    machine: MachineDef,             // リール配列・ペイライン・配当表などのマシン定義
+    reels: Vec<Reel>,                // リールを左から順に管理
+    frame: Frame,                    // 描画に使う全リールのスナップショット（ティックごとに取り直す）
+    last_spinning_state: Vec<bool>,  // 前回の各リールの回転状態（すべり中を含む、状態変化検出用）
+    wallet: Wallet,                  // 所持クレジットとベット設定
+    awaiting_payout: bool,           // ベット済みで配当の精算待ちかどうか
+    last_result: Option<Vec<LineWin>>, // 直前のゲームの当選ライン（未プレイならNone）
+    last_scatters: Vec<ScatterWin>,  // 直前のゲームのスキャッタの当選
+    message: Option<String>,         // 結果表示エリアに出すお知らせ（クレジット不足など）
+    show_help: bool,                 // 配当表（ヘルプ画面）を表示中かどうか
+    rng: SessionRng,                 // セッションの乱数生成器（シードから再現可能）
+    outcome: Option<Outcome>,        // 回転中のゲームの内部抽選結果（抽選モードのみ）
+    setting: usize,                  // 設定（1〜6、設定のないマシンは1）
+    stats: Option<SettingStats>,     // 設定推測用の集計（設定のあるマシンのみ）
+    state: GameState,                // 通常時 / BIG中 / REG中
+    transition: Option<Transition>,  // 直前のゲームで起きた状態の切り替わり（ボーナスの開始・終了）
+    free_spins: Option<FreeSpins>,   // 消化中のフリースピン（なければNone）
+    free_spin_event: Option<FreeSpinEvent>, // 直前のゲームで起きたフリースピンの開始・追加・終了
+    cascade: Vec<CascadeStep>,       // 直前のゲームの連鎖（連鎖しなかったゲームは空）
+    cascade_frame: usize,            // 表示中の連鎖のコマ（偶数: 盤面、奇数: 当選したマスが消えるところ）
+    cascade_frame_at: Option<u64>,   // 表示中のコマを出し始めた時刻（ミリ秒、演出の開始前はNone）
+    holds: Option<Vec<bool>>,        // ホールドの権利があればリールごとのホールド状態（なければNone）
+    held: Vec<bool>,                 // 回転中のゲームでホールドしたリール（2ゲーム続けてはホールドできない）
+    nudges: u32,                     // 残りのナッジ回数
+    gamble: Option<Gamble>,          // 直前の当たりのダブルアップ（次のゲームまで結果を残す）
+    jackpot: Option<JackpotPool>,    // ジャックポットの積立ファイル（ライブプレイのジャックポットのあるマシンのみ）
+    jackpot_amount: Option<u64>,     // 画面に見せる積立額（読み込む前・ジャックポットのないマシンはNone）
+    jackpot_checked_at: Option<u64>, // 積立額を最後に読んだ時刻（ミリ秒）
+    jackpot_generation: Option<u64>, // 回転中のゲームが狙っている積立の世代（回転の開始時に読む）
+    last_jackpot: u64,               // 直前のゲームで受け取ったジャックポット（なければ0）
+    jackpot_taken: bool,             // 直前のゲームで揃えたジャックポットを別のセッションが先に受け取っていた
+    unlogged_jackpot: Option<u64>,   // 受け取ったがセッションログにまだ書いていないジャックポット
+}
+
+/// 操作を適用した結果（描画やログ記録の判断に使う）
+pub enum Applied {
+    Nothing,              // 特になし（状態の変化は描画側で検出する）
+    Started,              // リールの回転を開始した
+    Settled(SpinResult),  // 全リールが停止して精算した
+}
+
+/// 1回の描画に使うゲームの状態
+/// 描画する側（Renderer）はこれだけを読み、ゲームの進行には手を出さない
+/// すべり終わるまで結果を隠すなど、見せ方の判断は済ませた値を入れる
+pub struct Snapshot<'a> {
+    pub machine: &'a MachineDef,             // マシン定義
+    pub reels: &'a [Reel],                   // リール（左から順）
+    pub frame: &'a Frame,                    // 全リールの同じ時刻のスナップショット
+    pub wallet: &'a Wallet,                  // 所持クレジットとベット設定
+    pub last_result: Option<&'a [LineWin]>,  // 直前のゲームの当選ライン（未プレイ・回転中はNone）
+    pub last_scatters: &'a [ScatterWin],     // 直前のゲームのスキャッタの当選
+    pub message: Option<&'a str>,            // 結果表示エリアに出すお知らせ
+    pub cascade: &'a [CascadeStep],          // 直前のゲームの連鎖
+    pub displayed_cascade_step: Option<(usize, bool)>, // 表示中の連鎖の段階（当選したマスが消えるところならtrue）
+    pub holds: Option<&'a [bool]>,           // ホールドの権利があればリールごとのホールド状態
+    pub held: &'a [bool],                    // 回転中のゲームでホールドしたリール
+    pub nudges: u32,                         // 残りのナッジ回数
+    pub gamble: Option<&'a Gamble>,          // 直前の当たりのダブルアップ
+    pub jackpot_amount: Option<u64>,         // ジャックポットの積立額
+    pub last_jackpot: u64,                   // 直前のゲームで受け取ったジャックポット
+    pub jackpot_taken: bool,                 // 揃えたジャックポットを別のセッションが先に受け取っていた
+    pub free_spins: Option<FreeSpins>,       // 消化中のフリースピン
+    pub free_spin_event: Option<FreeSpinEvent>, // 直前のゲームで起きたフリースピンの開始・追加・終了
+    pub displayed_free_spins: bool,          // フリースピン中として見せるかどうか
+    pub state: GameState,                    // 通常時 / BIG中 / REG中
+    pub transition: Option<Transition>,      // 直前のゲームで起きた状態の切り替わり
+    pub displayed_bonus: Option<BonusKind>,  // ボーナス中として見せる種類
+    pub stats: Option<&'a SettingStats>,     // 設定推測用の集計
+}
+
+impl SlotMachine {
+    /// 新しいスロットマシンインスタンスを作成
+    /// 各リールには左から0, 1, 2…のIDを割り当て
+    /// 有効ライン数の初期値は選択肢の最大値
+    /// リールの初期位置はセッションの乱数で決める
+    /// 設定のあるマシンでは設定も乱数で決める（指定があれば指定を優先）
+    ///
+    /// # 引数
+    /// * `machine` - マシン定義
+    /// * `rng` - セッションの乱数生成器
+    /// * `setting` - ホール側が指定した設定（Noneなら隠して抽選）
+    pub fn new(machine: MachineDef, mut rng: SessionRng, setting: Option<usize>) -> Self {
+        let max_lines = machine.line_options[machine.line_options.len() - 1];
+        let reels: Vec<Reel> = (0..machine.reels.len())
+            .map(|reel_id| Reel::new(reel_id, &machine, rng.gen_range(0..machine.reels[reel_id].len())))
+            .collect();
+        // 指定の有無にかかわらず抽選する（リプレイで乱数列がずれないように）
+        let drawn = if machine.has_settings() { rng.gen_range(1..=SETTINGS) } else { 1 };
+        let stats = machine
+            .has_settings()
+            .then(|| SettingStats::new(machine.lottery(1).map_or(0, |lottery| lottery.entries().len())));
+        Self {
+            last_spinning_state: vec![false; reels.len()],
+            frame: Frame::capture(&reels, 0),
+            reels,
+            wallet: Wallet::new(INITIAL_CREDITS, max_lines),
+            machine,
+            awaiting_payout: false,
+            last_result: None,
+            last_scatters: Vec::new(),
+            message: None,
+            show_help: false,
+            rng,
+            outcome: None,
+            setting: setting.unwrap_or(drawn),
+            stats,
+            state: GameState::Normal,
+            transition: None,
+            free_spins: None,
+            free_spin_event: None,
+            cascade: Vec::new(),
+            cascade_frame: 0,
+            cascade_frame_at: None,
+            holds: None,
+            held: Vec::new(),
+            nudges: 0,
+            gamble: None,
+            jackpot: None,
+            jackpot_amount: None,
+            jackpot_checked_at: None,
+            jackpot_generation: None,
+            last_jackpot: 0,
+            jackpot_taken: false,
+            unlogged_jackpot: None,
+        }
+    }
+
+    /// ジャックポットの積立ファイルにつなぐ（ライブプレイのみ）
+    /// リプレイでは積立に参加せず、受け取ったジャックポットはログの記録から加算する
+    pub fn connect_jackpot(&mut self) {
+        self.jackpot = self.machine.jackpot.as_ref().map(JackpotPool::new);
+        self.read_jackpot();
+    }
+
+    /// 操作を1つ適用する（描画は行わない）
+    /// ライブプレイとリプレイで同じ処理を通すことで、同じ入力から同じ結果を再現する
+    ///
+    /// # 引数
+    /// * `command` - 適用する操作
+    /// * `now_ms` - 操作の時刻（セッション開始からのミリ秒）
+    pub fn apply(&mut self, command: ReelCommand, now_ms: u64) -> Applied {
+        match command {
+            ReelCommand::StartAll => {
+                if self.start_all_reels(now_ms) {
+                    return Applied::Started;
+                }
+            }
+            ReelCommand::Stop(idx) => self.stop_reel(idx, now_ms),
+            ReelCommand::StopNext => self.stop_next_reel(now_ms),
+            ReelCommand::Hold(idx) => self.toggle_hold(idx),
+            ReelCommand::NudgeUp(idx) | ReelCommand::NudgeDown(idx) => {
+                if let Some(result) = self.nudge(idx, command == ReelCommand::NudgeUp(idx)) {
+                    return Applied::Settled(result);
+                }
+            }
+            ReelCommand::Gamble(guess) => {
+                if let Some(result) = self.play_gamble(guess) {
+                    return Applied::Settled(result);
+                }
+            }
+            ReelCommand::Collect => self.collect(),
+            ReelCommand::ChangeBet => self.change_bet(),
+            ReelCommand::ChangeLines => self.change_lines(),
+            ReelCommand::ToggleHelp => self.toggle_help(),
+            ReelCommand::Exit => {}
+        }
+        // 最後のリールが止まった操作の時点で精算する（次の操作より必ず先に精算される）
+        match self.settle_if_stopped() {
+            Some(result) => Applied::Settled(result),
+            None => Applied::Nothing,
+        }
+    }
+
+    /// 全てのリールの回転を開始する
+    /// 回転開始前にベット額を差し引き、クレジットが足りなければ回転しない
+    ///
+    /// # 戻り値
+    /// 回転を開始した場合はtrue
+    fn start_all_reels(&mut self, now_ms: u64) -> bool {
+        // ヘルプ表示中、回転中・精算前の再スタートは受け付けない（二重ベット防止）
+        if self.show_help || self.awaiting_payout || self.reels.iter().any(|reel| reel.is_spinning()) {
+            return false;
+        }
+        // フリースピン中はベットなしで回す（掛け枚数・ライン数は始まったときのまま）
+        if self.free_spins.is_some() {
+            self.wallet.start_free_spin();
+        } else if let Err(err) = self.wallet.place_bet() {
+            self.message = Some(err.to_string());
+            return false;
+        }
+        self.awaiting_payout = true;
+        self.last_result = None;
+        self.last_scatters.clear();
+        self.message = None;
+        self.transition = None;
+        self.free_spin_event = None;
+        self.last_jackpot = 0;
+        self.jackpot_taken = false;
+        self.cascade.clear();
+        self.cascade_frame = 0;
+        self.cascade_frame_at = None;
+        // 続けられるダブルアップは受け取ったものとして終える
+        self.gamble = None;
+        // ホールドしたリールは回さない（ナッジは次のゲームに持ち越さない）
+        self.held = self.holds.take().unwrap_or_default();
+        self.nudges = 0;
+        self.contribute_jackpot();
+        // 抽選モードでは回転開始時に結果を決めておく（停止位置はこの結果に合わせる）
+        // ボーナス中はボーナス専用の抽選テーブルを使う
+        let max_lines = self.machine.max_lines();
+        self.outcome = self
+            .state
+            .lottery(&self.machine, self.setting)
+            .map(|lottery| lottery.draw(&mut self.rng, self.wallet.lines(), max_lines));
+
+        // 全リールの回転開始フラグを設定
+        for (index, reel) in self.reels.iter_mut().enumerate() {
+            if self.held.get(index) != Some(&true) {
+                reel.start_spinning(now_ms);
+            }
+        }
+        true
+    }
+
+    /// 全リールを指定時刻で読み、描画に使うスナップショットを取り直す（ライブプレイのゲームループから毎ティック呼ぶ）
+    /// 停止位置は停止操作の時刻で決まるため、表示の進み具合は結果に影響しない
+    pub fn tick(&mut self, now_ms: u64) {
+        self.frame = Frame::capture(&self.reels, now_ms);
+    }
+
+    /// 指定されたインデックスのリールを停止する
+    /// index: 停止するリールの番号（0:左端 〜 リール数-1:右端）
+    /// 抽選モードでは押された位置から最大すべりコマ数の範囲で、抽選結果に合う位置まですべらせて止める
+    fn stop_reel(&mut self, index: usize, now_ms: u64) {
+        let Some(reel) = self.reels.get(index) else {
+            return;
+        };
+        if !reel.is_spinning() {
+            return;
+        }
+        let pressed = reel.position_at(now_ms);
+        let position = match self.outcome {
+            Some(outcome) => {
+                let stops: Vec<Option<usize>> = self
+                    .reels
+                    .iter()
+                    .map(|reel| (!reel.is_spinning()).then(|| reel.position()))
+                    .collect();
+                let paylines = self.machine.paylines.active(self.wallet.lines());
+                self.machine.slip.choose_stop(&self.machine, paylines, outcome, &stops, index, pressed)
+            }
+            None => pressed,
+        };
+        self.reels[index].stop_at(position, now_ms);
+    }
+
+    /// 回転中のリールのうち最も左のものを停止する（Enterキーの順押し用）
+    fn stop_next_reel(&mut self, now_ms: u64) {
+        if let Some(index) = self.reels.iter().position(|reel| reel.is_spinning()) {
+            self.stop_reel(index, now_ms);
+        }
+    }
+
+    /// リールボタン（数字キー・矢印キー）の操作を今の状態に合わせて読み替える
+    /// 回転中は停止、全リールが止まっていてホールドの権利があるときはホールドの切り替えになる
+    /// 読み替えた後の操作をセッションログに記録するため、リプレイでは読み替えずに適用される
+    pub fn resolve(&self, command: ReelCommand) -> ReelCommand {
+        match command {
+            ReelCommand::Stop(index) if self.holds.is_some() && !self.reels.iter().any(|reel| reel.is_spinning()) => {
+                ReelCommand::Hold(index)
+            }
+            _ => command,
+        }
+    }
+
+    /// 指定したリールのホールドを切り替える
+    /// すべてのリールはホールドできない（少なくとも1つは回す）
+    fn toggle_hold(&mut self, index: usize) {
+        let reels = self.reels.len();
+        let Some(holds) = &mut self.holds else {
+            return;
+        };
+        if index >= reels || (!holds[index] && holds.iter().filter(|&&held| held).count() + 1 >= reels) {
+            return;
+        }
+        holds[index] = !holds[index];
+    }
+
+    /// 止まっているリールを1コマ動かし、動かした後の盤面で精算し直す（ナッジ）
+    /// 当たりになればその配当を払い、残りのナッジとホールドの権利はなくなる
+    ///
+    /// # 戻り値
+    /// ナッジした場合は停止位置と獲得クレジット
+    fn nudge(&mut self, index: usize, up: bool) -> Option<SpinResult> {
+        if self.nudges == 0 || self.awaiting_payout || index >= self.reels.len() {
+            return None;
+        }
+        self.nudges -= 1;
+        self.reels[index].nudge(up);
+        let window: Vec<Vec<SymbolId>> = self.reels.iter().map(|reel| reel.get_visible_symbols()).collect();
+        let wins = evaluate(
+            &self.machine,
+            &window,
+            self.machine.paylines.active(self.wallet.lines()),
+            self.wallet.coins_per_line(),
+        );
+        let scatters = scatter::evaluate_scatters(&window, &self.machine, self.wallet.bet());
+        let payout = wins.iter().map(|win| win.payout).sum::<u64>()
+            + scatters.iter().map(|win| win.payout).sum::<u64>();
+        self.wallet.credit(payout);
+        self.award_jackpot(&wins);
+        if payout > 0 {
+            self.nudges = 0;
+            self.holds = None;
+            self.offer_gamble(payout);
+        }
+        self.last_result = Some(wins);
+        self.last_scatters = scatters;
+        self.message = None;
+        Some(SpinResult {
+            stops: self.reels.iter().map(|reel| reel.position()).collect(),
+            payout,
+        })
+    }
+
+    /// ハズレのゲームのあとに、次のゲームまでのホールド・ナッジの権利を抽選する
+    /// ホールドは直前のゲームでホールドを使っていた場合は抽選しない
+    fn offer_holds_and_nudges(&mut self, payout: u64) {
+        let held_last_spin = std::mem::take(&mut self.held).contains(&true);
+        if payout > 0 {
+            return;
+        }
+        if let Some(nudge) = &self.machine.nudge {
+            if hold::draw(nudge.chance, &mut self.rng) {
+                self.nudges = nudge.count;
+            }
+        }
+        if let Some(def) = &self.machine.hold {
+            if !held_last_spin && hold::draw(def.chance, &mut self.rng) {
+                self.holds = Some(vec![false; self.reels.len()]);
+            }
+        }
+    }
+
+    /// 当たりのあとにダブルアップを始められるようにする
+    /// フリースピン・ボーナスの配当は別に数えるため、通常時のゲームのみ
+    fn offer_gamble(&mut self, payout: u64) {
+        if payout == 0
+            || self.machine.gamble.is_none()
+            || self.free_spins.is_some()
+            || self.free_spin_event.is_some()
+            || self.state.run().is_some()
+            || self.transition.is_some()
+        {
+            return;
+        }
+        self.gamble = Some(Gamble::new(payout));
+    }
+
+    /// ダブルアップの予想を1回行う（カードはセッションの乱数で引く）
+    /// 当たれば獲得クレジットが倍になり、外れると没収される
+    ///
+    /// # 戻り値
+    /// 予想した場合は停止位置と、ダブルアップ後のこのゲームの獲得クレジット
+    fn play_gamble(&mut self, guess: Guess) -> Option<SpinResult> {
+        if self.awaiting_payout {
+            return None;
+        }
+        let def = self.machine.gamble.as_ref()?;
+        let gamble = self.gamble.as_mut().filter(|gamble| gamble.open)?;
+        let draw = gamble.play(def, guess, &mut self.rng);
+        self.wallet.replace_win(draw.stake, draw.result);
+        Some(SpinResult {
+            stops: self.reels.iter().map(|reel| reel.position()).collect(),
+            payout: draw.result,
+        })
+    }
+
+    /// ダブルアップをやめて、その時点の獲得クレジットを受け取る
+    fn collect(&mut self) {
+        if let Some(gamble) = &mut self.gamble {
+            gamble.open = false;
+        }
+    }
+
+    /// 回転の開始時にベットの一部を積み立て、この回転が狙う積立の世代を覚えておく
+    /// フリースピンはベットがないので積み立てず、世代だけ読む
+    fn contribute_jackpot(&mut self) {
+        let (Some(pool), Some(def)) = (&self.jackpot, &self.machine.jackpot) else {
+            return;
+        };
+        let state = if self.free_spins.is_some() {
+            pool.state()
+        } else {
+            pool.contribute(self.wallet.bet(), def.contribution)
+        };
+        match state {
+            Ok(state) => {
+                self.jackpot_amount = Some(state.amount);
+                self.jackpot_generation = Some(state.generation);
+            }
+            Err(err) => {
+                self.jackpot_generation = None;
+                self.message = Some(format!("ジャックポットの積立ファイルを更新できません: {}", err));
+            }
+        }
+    }
+
+    /// ジャックポットの役が揃っていれば積立額を受け取る
+    /// 回転を始めた時点の世代を積立ファイルのロックの中で照らし合わせ、
+    /// 同時に揃った別のセッションが先に受け取っていれば何も受け取らず、そのことを結果と一緒に見せる
+    /// （積立ファイルを読めずに始めた回転では受け取らない）
+    fn award_jackpot(&mut self, wins: &[LineWin]) {
+        let (Some(pool), Some(def), Some(generation)) = (&self.jackpot, &self.machine.jackpot, self.jackpot_generation) else {
+            return;
+        };
+        if !wins.iter().any(|win| win.entry == def.entry) {
+            return;
+        }
+        match pool.win(generation) {
+            Ok(Some(amount)) => {
+                self.wallet.deposit(amount);
+                self.last_jackpot = amount;
+                self.unlogged_jackpot = Some(amount);
+                self.read_jackpot();
+            }
+            // 先に受け取られていた（初期値に戻った積立額を見せる）
+            Ok(None) => {
+                self.jackpot_taken = true;
+                self.read_jackpot();
+            }
+            Err(err) => self.message = Some(format!("ジャックポットの積立ファイルを更新できません: {}", err)),
+        }
+    }
+
+    /// 受け取ったジャックポットのうち、セッションログにまだ書いていない額を取り出す
+    pub fn take_unlogged_jackpot(&mut self) -> Option<u64> {
+        self.unlogged_jackpot.take()
+    }
+
+    /// ジャックポットの積立額を定期的に読み直す（ライブプレイの描画ループから呼ぶ）
+    /// 前回から JACKPOT_POLL 経っていなければ読まない
+    ///
+    /// # 引数
+    /// * `now_ms` - 現在の時刻（セッション開始からのミリ秒）
+    ///
+    /// # 戻り値
+    /// 画面に見せる積立額が変わった場合はtrue
+    pub fn poll_jackpot(&mut self, now_ms: u64) -> bool {
+        if self.jackpot.is_none()
+            || self
+                .jackpot_checked_at
+                .is_some_and(|checked_at| now_ms.saturating_sub(checked_at) < JACKPOT_POLL.as_millis() as u64)
+        {
+            return false;
+        }
+        self.jackpot_checked_at = Some(now_ms);
+        self.read_jackpot()
+    }
+
+    /// ジャックポットの積立額を読み直す（他のセッションの積立・当選を反映する）
+    ///
+    /// # 戻り値
+    /// 画面に見せる積立額が変わった場合はtrue
+    fn read_jackpot(&mut self) -> bool {
+        let Some(pool) = &self.jackpot else {
+            return false;
+        };
+        // 読めないときは前の表示のままにする（書き込み時のエラーはお知らせで伝える）
+        let Ok(PoolState { amount, .. }) = pool.state() else {
+            return false;
+        };
+        let changed = self.jackpot_amount != Some(amount);
+        self.jackpot_amount = Some(amount);
+        changed
+    }
+
+    /// ベットの掛け枚数を切り替える
+    /// 回転中・フリースピン中はベット額を変更できない
+    fn change_bet(&mut self) {
+        if self.awaiting_payout || self.free_spins.is_some() {
+            return;
+        }
+        self.wallet.cycle_coins_per_line();
+        self.message = None;
+    }
+
+    /// 有効ライン数をマシン定義の選択肢の順に切り替える（標準は1→3→5→7→1）
+    /// 回転中・フリースピン中はベット額を変更できない
+    fn change_lines(&mut self) {
+        if self.awaiting_payout || self.free_spins.is_some() {
+            return;
+        }
+        self.wallet.cycle_lines(&self.machine.line_options);
+        self.message = None;
+    }
+
+    /// 全リール停止後に当選判定を行い、配当をクレジットに加算する
+    /// 1回のゲームにつき1度だけ精算する
+    ///
+    /// # 戻り値
+    /// 精算を行った場合は停止位置と獲得クレジット
+    fn settle_if_stopped(&mut self) -> Option<SpinResult> {
+        if !self.awaiting_payout || self.reels.iter().any(|reel| reel.is_spinning()) {
+            return None;
+        }
+        let window: Vec<Vec<SymbolId>> = self.reels.iter().map(|reel| reel.get_visible_symbols()).collect();
+        let stops: Vec<usize> = self.reels.iter().map(|reel| reel.position()).collect();
+        // カスケードのマシンは当選がなくなるまでの連鎖をここでまとめて計算する（画面ではすべり終わってから順に見せる）
+        let mut steps = cascade::run(
+            &self.machine,
+            &stops,
+            self.machine.paylines.active(self.wallet.lines()),
+            self.wallet.coins_per_line(),
+        );
+        let wins: Vec<LineWin> = steps.iter().flat_map(|step| step.wins.iter().cloned()).collect();
+        if steps.len() == 1 {
+            steps.clear();
+        }
+        self.cascade = steps;
+        let scatters = scatter::evaluate_scatters(&window, &self.machine, self.wallet.bet());
+        let payout = wins.iter().map(|win| win.payout).sum::<u64>()
+            + scatters.iter().map(|win| win.payout).sum::<u64>();
+        // フリースピン中の配当は別枠に貯め、終了時にまとめて残高に加算する
+        if self.free_spins.is_some() {
+            self.wallet.record_free_spin_win(payout);
+        } else {
+            self.wallet.credit(payout);
+        }
+        if let Some(def) = &self.machine.free_spins {
+            self.free_spin_event = scatter::advance(&mut self.free_spins, def, &window, payout);
+            if let Some(FreeSpinEvent::Ended { win, .. }) = self.free_spin_event {
+                self.wallet.deposit(win);
+            }
+        }
+        self.last_scatters = scatters;
+        self.award_jackpot(&wins);
+        // 設定推測は通常時の抽選テーブルで引いたゲームのみ集計する
+        if let (Some(stats), GameState::Normal) = (&mut self.stats, self.state) {
+            stats.record(&self.machine, &wins, self.wallet.lines());
+        }
+        // ボーナスの役が揃えばボーナス開始、ボーナス中はゲーム数と払い出しを数える
+        self.transition = self.state.advance(&self.machine, &wins, self.wallet.coins_per_line());
+        self.last_result = Some(wins);
+        self.awaiting_payout = false;
+        self.outcome = None;
+        self.offer_holds_and_nudges(payout);
+        self.offer_gamble(payout);
+        Some(SpinResult { stops, payout })
+    }
+
+    /// リールの回転状態が前回と変化したかを確認
+    /// 表示の更新が必要かどうかを判断するために使用
+    pub fn has_state_changed(&mut self) -> bool {
+        // 現在の回転状態を取得（すべり終わりも描画のきっかけにする）
+        let current_state: Vec<bool> = self
+            .frame
+            .reels
+            .iter()
+            .map(|reel| reel.spinning || reel.sliding)
+            .collect();
+        
+        // 前回の状態と比較
+        let changed = current_state != self.last_spinning_state;
+        // 今回の状態を保存
+        self.last_spinning_state = current_state;
+        changed
+    }
+
+    /// 画面に見せるカスケードの段階（連鎖しなかったゲーム・すべり終わる前はNone）
+    ///
+    /// # 戻り値
+    /// Some((段階の添字, 当選したマスが消えるところかどうか))
+    fn displayed_cascade_step(&self) -> Option<(usize, bool)> {
+        if self.cascade.is_empty() || self.frame.is_moving() {
+            return None;
+        }
+        Some((self.cascade_frame / 2, self.cascade_frame % 2 == 1))
+    }
+
+    /// カスケードの演出を時間に合わせて1コマ進める（ライブプレイの描画ループから呼ぶ）
+    /// 盤面と消えるところを交互に見せ、当選のなくなった最後の盤面で止まる
+    /// 精算は最後のリールを止めた時点で済んでいるため、演出の進み具合は結果に影響しない
+    ///
+    /// # 引数
+    /// * `now_ms` - 現在の時刻（セッション開始からのミリ秒）
+    ///
+    /// # 戻り値
+    /// 表示するコマが変わった場合はtrue
+    pub fn advance_cascade(&mut self, now_ms: u64) -> bool {
+        let frames = (2 * self.cascade.len()).saturating_sub(1);
+        if self.cascade_frame + 1 >= frames || self.displayed_cascade_step().is_none() {
+            return false;
+        }
+        // すべり終わった時点から演出を始める
+        let Some(shown_at) = self.cascade_frame_at else {
+            self.cascade_frame_at = Some(now_ms);
+            return false;
+        };
+        if now_ms.saturating_sub(shown_at) < CASCADE_FRAME.as_millis() as u64 {
+            return false;
+        }
+        self.cascade_frame += 1;
+        self.cascade_frame_at = Some(now_ms);
+        true
+    }
+
+    /// 画面に見せるボーナスの種類（通常時はNone）
+    /// 状態は最後のリールを止めた時点で切り替わるが、すべり終わるまでは切り替わる前の状態を見せる
+    fn displayed_bonus(&self) -> Option<BonusKind> {
+        if !self.frame.any_sliding() {
+            return self.state.kind();
+        }
+        match self.transition {
+            Some(Transition::Started(_)) => None,
+            Some(Transition::Ended { kind, .. }) => Some(kind),
+            None => self.state.kind(),
+        }
+    }
+
+    /// 画面に見せる状態がフリースピン中かどうか
+    /// ボーナスと同じく、すべり終わるまでは切り替わる前の状態を見せる
+    fn displayed_free_spins(&self) -> bool {
+        if !self.frame.any_sliding() {
+            return self.free_spins.is_some();
+        }
+        match self.free_spin_event {
+            Some(FreeSpinEvent::Awarded(_)) => false,
+            Some(FreeSpinEvent::Ended { .. }) => true,
+            _ => self.free_spins.is_some(),
+        }
+    }
+
+    /// ヘルプ画面の表示/非表示を切り替える
+    /// 回転中は切り替えない
+    fn toggle_help(&mut self) {
+        if self.awaiting_payout {
+            return;
+        }
+        self.show_help = !self.show_help;
+    }
+
+    // === フロントエンド向けの読み取り ===
+
+    /// 描画に使う状態をまとめて取り出す（tick で取り直したスナップショットを含む）
+    pub fn snapshot(&self) -> Snapshot<'_> {
+        Snapshot {
+            machine: &self.machine,
+            reels: &self.reels,
+            frame: &self.frame,
+            wallet: &self.wallet,
+            last_result: self.last_result.as_deref(),
+            last_scatters: &self.last_scatters,
+            message: self.message.as_deref(),
+            cascade: &self.cascade,
+            displayed_cascade_step: self.displayed_cascade_step(),
+            holds: self.holds.as_deref(),
+            held: &self.held,
+            nudges: self.nudges,
+            gamble: self.gamble.as_ref(),
+            jackpot_amount: self.jackpot_amount,
+            last_jackpot: self.last_jackpot,
+            jackpot_taken: self.jackpot_taken,
+            free_spins: self.free_spins,
+            free_spin_event: self.free_spin_event,
+            displayed_free_spins: self.displayed_free_spins(),
+            state: self.state,
+            transition: self.transition,
+            displayed_bonus: self.displayed_bonus(),
+            stats: self.stats.as_ref(),
+        }
+    }
+
+    /// マシン定義
+    pub fn machine(&self) -> &MachineDef {
+        &self.machine
+    }
+
+    /// リール（左から順）
+    pub fn reels(&self) -> &[Reel] {
+        &self.reels
+    }
+
+    /// 描画に使う全リールのスナップショット（tick で取り直す）
+    pub fn frame(&self) -> &Frame {
+        &self.frame
+    }
+
+    /// 所持クレジットとベット設定
+    pub fn wallet(&self) -> &Wallet {
+        &self.wallet
+    }
+
+    /// 配当表（ヘルプ画面）を表示中かどうか
+    pub fn show_help(&self) -> bool {
+        self.show_help
+    }
+
+    /// 設定推測用の集計（設定のあるマシンのみ）
+    pub fn stats(&self) -> Option<&SettingStats> {
+        self.stats.as_ref()
+    }
+
+    /// 設定（1〜6、設定のないマシンは1）
+    pub fn setting(&self) -> usize {
+        self.setting
+    }
+
+    /// セッションのシード（ログに記録してリプレイに使う）
+    pub fn seed(&self) -> u64 {
+        self.rng.seed()
+    }
+
+    /// セッションログに記録されたジャックポットを払い出す（リプレイ用）
+    /// リプレイでは積立ファイルを読まないため、受け取った額はログから戻す
+    pub fn deposit_jackpot(&mut self, amount: u64) {
+        self.wallet.deposit(amount);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::ManualClock;
+    use crate::machine::MachineDef;
+    use crate::ticker::{Ticker, FRAME};
+    use rand::Rng;
+
+    /// 組み込みマシンと、手で進める時計のゲームループを用意する
+    fn game() -> (SlotMachine, Ticker<ManualClock>) {
+        let slot_machine = SlotMachine::new(MachineDef::builtin(), SessionRng::seeded(1), None);
+        (slot_machine, Ticker::new(ManualClock::new(), FRAME))
+    }
+
+    /// ゲームループをNティック進め、その時刻のスナップショットを取る
+    fn run_ticks(slot_machine: &mut SlotMachine, ticker: &mut Ticker<ManualClock>, ticks: usize) -> u64 {
+        for _ in 0..ticks {
+            ticker.wait();
+        }
+        let now_ms = ticker.now_ms();
+        slot_machine.tick(now_ms);
+        now_ms
+    }
+
+    /// ホールド・ナッジ・ダブルアップ・ジャックポットのあるクラシックマシン（3リール×1段・1ライン）
+    fn classic() -> MachineDef {
+        MachineDef::from_toml(
+            include_str!("../machines/classic-3x1.toml"),
+            std::path::Path::new("machines/classic-3x1.toml"),
+        )
+        .unwrap()
+    }
+
+    /// 回転中のリールを左から順に、指定した停止位置を指している時刻に止める（すべりのないマシン用）
+    ///
+    /// # 引数
+    /// * `started_ms` - 回し始めた時刻
+    /// * `targets` - 左から順に止めるリールの停止位置
+    fn stop_reels_at(slot_machine: &mut SlotMachine, started_ms: u64, targets: &[usize]) -> Applied {
+        let step = slot_machine.machine.spin_step.as_millis() as u64;
+        let mut applied = Applied::Nothing;
+        for (index, &target) in targets.iter().enumerate() {
+            let reel = &slot_machine.reels[index];
+            if !reel.is_spinning() {
+                continue;
+            }
+            let len = slot_machine.machine.reels[index].len();
+            let steps = ((target + len - reel.position()) % len) as u64;
+            applied = slot_machine.apply(ReelCommand::Stop(index), started_ms + steps * step);
+        }
+        applied
+    }
+
+    /// 各リールの画面上の位置
+    fn displayed(slot_machine: &SlotMachine) -> Vec<usize> {
+        slot_machine.frame.reels.iter().map(|reel| reel.display).collect()
+    }
+
+    /// 組み込みマシンのリールが1コマ進む間隔（ミリ秒）
+    fn step_ms() -> u64 {
+        MachineDef::builtin().spin_step.as_millis() as u64
+    }
+
+    /// 組み込みマシンのリール1周のコマ数（どのリールも同じ）
+    fn strip_len() -> usize {
+        let machine = MachineDef::builtin();
+        assert!(machine.reels.iter().all(|strip| strip.len() == machine.reels[0].len()));
+        machine.reels[0].len()
+    }
+
+    /// 1コマ進むのにかかるティック数（組み込みマシンの間隔はティックの整数倍）
+    fn ticks_per_step() -> usize {
+        let frame_ms = FRAME.as_millis() as u64;
+        assert_eq!(step_ms() % frame_ms, 0);
+        (step_ms() / frame_ms) as usize
+    }
+
+    /// 回し始めた位置からNコマ進んだ位置
+    fn advanced(origins: &[usize], steps: usize) -> Vec<usize> {
+        origins.iter().map(|origin| (origin + steps) % strip_len()).collect()
+    }
+
+    #[test]
+    fn reels_turn_once_in_a_strip_length_of_steps() {
+        let (mut slot_machine, mut ticker) = game();
+        let per_step = ticks_per_step();
+        let origins: Vec<usize> = slot_machine.reels.iter().map(|reel| reel.position()).collect();
+        slot_machine.apply(ReelCommand::StartAll, ticker.now_ms());
+
+        // 1コマの間隔（組み込みマシンは1ティック＝35ミリ秒）で1コマ進む
+        run_ticks(&mut slot_machine, &mut ticker, per_step);
+        assert_eq!(displayed(&slot_machine), advanced(&origins, 1));
+        run_ticks(&mut slot_machine, &mut ticker, 9 * per_step);
+        assert_eq!(displayed(&slot_machine), advanced(&origins, 10));
+        // リールのコマ数だけ進むと1周（組み込みマシンは21コマで0.735秒）
+        let now_ms = run_ticks(&mut slot_machine, &mut ticker, (strip_len() - 10) * per_step);
+        assert_eq!(now_ms, strip_len() as u64 * step_ms());
+        assert_eq!(displayed(&slot_machine), origins);
+        assert!(slot_machine.frame.any_spinning());
+    }
+
+    #[test]
+    fn late_ticks_do_not_change_the_spin_speed() {
+        let (mut slot_machine, mut ticker) = game();
+        let per_step = ticks_per_step();
+        let origins: Vec<usize> = slot_machine.reels.iter().map(|reel| reel.position()).collect();
+        slot_machine.apply(ReelCommand::StartAll, ticker.now_ms());
+        run_ticks(&mut slot_machine, &mut ticker, 2 * per_step);
+        // 描画が遅れて3コマ分止まっても、位置は経過時間のとおりに進む
+        ticker.clock().advance(3 * step_ms());
+        run_ticks(&mut slot_machine, &mut ticker, per_step);
+        assert_eq!(ticker.now_ms(), 6 * step_ms());
+        assert_eq!(displayed(&slot_machine), advanced(&origins, 6));
+    }
+
+    #[test]
+    fn reels_stop_where_they_were_at_the_stop_tick() {
+        let (mut slot_machine, mut ticker) = game();
+        let per_step = ticks_per_step();
+        let origins: Vec<usize> = slot_machine.reels.iter().map(|reel| reel.position()).collect();
+        slot_machine.apply(ReelCommand::StartAll, ticker.now_ms());
+
+        let now_ms = run_ticks(&mut slot_machine, &mut ticker, 5 * per_step);
+        slot_machine.apply(ReelCommand::Stop(0), now_ms);
+        let now_ms = run_ticks(&mut slot_machine, &mut ticker, 3 * per_step);
+        // 止めたリールは動かず、残りのリールは回り続ける
+        assert_eq!(displayed(&slot_machine)[0], advanced(&origins, 5)[0]);
+        assert_eq!(displayed(&slot_machine)[1..], advanced(&origins, 8)[1..]);
+
+        slot_machine.apply(ReelCommand::Stop(1), now_ms);
+        let now_ms = run_ticks(&mut slot_machine, &mut ticker, 4 * per_step);
+        let Applied::Settled(result) = slot_machine.apply(ReelCommand::Stop(2), now_ms) else {
+            panic!("最後のリールを止めたら精算される");
+        };
+        assert_eq!(
+            result.stops,
+            vec![advanced(&origins, 5)[0], advanced(&origins, 8)[1], advanced(&origins, 12)[2]]
+        );
+        run_ticks(&mut slot_machine, &mut ticker, 1);
+        assert_eq!(displayed(&slot_machine), result.stops);
+        assert!(!slot_machine.frame.is_moving());
+    }
+
+    /// 操作とティックを乱数でつなぎ、ティックごとのスナップショットが直前のスナップショットから
+    /// 経過時間の分しか進まず（逆戻り・飛び越しがない）、精算した停止位置で止まって見えることを確かめる
+    #[test]
+    fn stress_snapshots_follow_the_applied_commands() {
+        let (mut slot_machine, mut ticker) = game();
+        let (step_ms, strip_len) = (step_ms(), strip_len());
+        let mut rng = SessionRng::seeded(20_240_602);
+        let mut previous = slot_machine.snapshot().frame.clone();
+        let mut settled: Option<Vec<usize>> = None;
+        let mut games = 0;
+        for _ in 0..20_000 {
+            // クレジットが尽きてゲームが止まらないように補充する
+            if slot_machine.wallet.balance() < slot_machine.wallet.bet() {
+                slot_machine.wallet.deposit(INITIAL_CREDITS);
+            }
+            let command = match rng.gen_range(0..4) {
+                0 => ReelCommand::StartAll,
+                1 => ReelCommand::StopNext,
+                2 => ReelCommand::Stop(rng.gen_range(0..slot_machine.reels.len())),
+                _ => ReelCommand::Collect,
+            };
+            match slot_machine.apply(command, ticker.now_ms()) {
+                Applied::Started => settled = None,
+                Applied::Settled(result) => {
+                    settled = Some(result.stops);
+                    games += 1;
+                }
+                Applied::Nothing => {}
+            }
+            // 描画の遅れでティックが間に合わないこともある
+            if rng.gen_range(0..10) == 0 {
+                ticker.clock().advance(rng.gen_range(0..100));
+            }
+            let now_ms = run_ticks(&mut slot_machine, &mut ticker, 1);
+
+            let snapshot = slot_machine.snapshot();
+            assert_eq!(snapshot.frame.at_ms, now_ms);
+            let max_steps = (now_ms - previous.at_ms) / step_ms + 1;
+            for (reel, (before, after)) in previous.reels.iter().zip(&snapshot.frame.reels).enumerate() {
+                let moved = (after.display + strip_len - before.display) % strip_len;
+                assert!(
+                    moved as u64 <= max_steps,
+                    "リール{}が{}ミリ秒で{}コマ動いた: {:?} → {:?}",
+                    reel,
+                    now_ms - previous.at_ms,
+                    moved,
+                    before,
+                    after
+                );
+            }
+            if let Some(stops) = &settled {
+                if !snapshot.frame.is_moving() {
+                    assert_eq!(&displayed(&slot_machine), stops, "精算した停止位置と画面が違う");
+                }
+            }
+            previous = snapshot.frame.clone();
+        }
+        assert!(games > 100, "精算まで進んだゲームが少なすぎる: {}", games);
+    }
+
+    #[test]
+    fn a_jackpot_taken_by_another_session_is_not_paid_twice() {
+        let mut machine = classic();
+        // テストごとに別の積立ファイルを使う（終わったら消す）
+        let name = format!("test-game-taken-{}", std::process::id());
+        let path = std::env::temp_dir().join(format!("gh-slot-jackpot-{}.pool", name));
+        let _ = std::fs::remove_file(&path);
+        let def = machine.jackpot.as_mut().unwrap();
+        def.pool = name;
+        let entry = def.entry;
+        let mut slot_machine = SlotMachine::new(machine, SessionRng::seeded(1), None);
+        slot_machine.connect_jackpot();
+
+        // 回し始めた時点の世代で、別のセッションが先にジャックポットを受け取った
+        slot_machine.apply(ReelCommand::StartAll, 0);
+        let other = JackpotPool::new(slot_machine.machine.jackpot.as_ref().unwrap());
+        let generation = slot_machine.jackpot_generation.unwrap();
+        assert!(other.win(generation).unwrap().is_some());
+
+        // 同じ世代で揃えても受け取らず、受け取られていたことを見せる
+        let balance = slot_machine.wallet.balance();
+        let win = LineWin { line: Some(0), entry, symbol: 0, count: 3, payout: 0, multiplier: 1, ways: 1 };
+        slot_machine.award_jackpot(&[win]);
+        let snapshot = slot_machine.snapshot();
+        assert_eq!(slot_machine.wallet.balance(), balance);
+        assert_eq!(snapshot.last_jackpot, 0);
+        assert!(snapshot.jackpot_taken);
+        assert_eq!(snapshot.jackpot_amount, Some(500));
+        assert_eq!(slot_machine.take_unlogged_jackpot(), None);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn collecting_keeps_the_gamble_stake() {
+        let mut slot_machine = SlotMachine::new(classic(), SessionRng::seeded(1), None);
+        // 20クレジットの当たりのあと
+        slot_machine.wallet.credit(20);
+        slot_machine.offer_gamble(20);
+        let balance = slot_machine.wallet.balance();
+
+        slot_machine.apply(ReelCommand::Collect, 0);
+        let gamble = slot_machine.gamble.unwrap();
+        assert_eq!((gamble.stake, gamble.open), (20, false));
+        // 受け取った後は予想できず、残高も獲得額も変わらない
+        assert!(matches!(slot_machine.apply(ReelCommand::Gamble(Guess::Red), 0), Applied::Nothing));
+        assert_eq!((slot_machine.wallet.balance(), slot_machine.wallet.last_win()), (balance, 20));
+    }
+
+    #[test]
+    fn holds_are_offered_after_a_full_stop_and_used_once() {
+        let mut machine = classic();
+        machine.hold.as_mut().unwrap().chance = 100;
+        machine.nudge = None;
+        let mut slot_machine = SlotMachine::new(machine, SessionRng::seeded(1), None);
+
+        // 回転中はホールドの権利がなく、リールボタンは停止のまま
+        slot_machine.apply(ReelCommand::StartAll, 0);
+        stop_reels_at(&mut slot_machine, 0, &[3, 1]);
+        assert!(slot_machine.holds.is_none());
+        assert_eq!(slot_machine.resolve(ReelCommand::Stop(2)), ReelCommand::Stop(2));
+        slot_machine.apply(ReelCommand::Hold(0), 0);
+        assert!(slot_machine.holds.is_none());
+
+        // ⬜⬜⬜のハズレで全リールが止まると、リールボタンがホールドの切り替えになる
+        let Applied::Settled(result) = stop_reels_at(&mut slot_machine, 0, &[3, 1, 1]) else {
+            panic!("最後のリールで精算されていない");
+        };
+        assert_eq!(result.payout, 0);
+        assert_eq!(slot_machine.holds.as_deref(), Some(&[false, false, false][..]));
+        assert_eq!(slot_machine.resolve(ReelCommand::Stop(0)), ReelCommand::Hold(0));
+        for index in 0..3 {
+            slot_machine.apply(ReelCommand::Hold(index), 0);
+        }
+        // すべてのリールはホールドできない
+        assert_eq!(slot_machine.holds.as_deref(), Some(&[true, true, false][..]));
+
+        // ホールドしたリールは次のゲームで回らず、権利は1回使うとなくなる
+        slot_machine.apply(ReelCommand::StartAll, 1000);
+        let spinning: Vec<bool> = slot_machine.reels.iter().map(|reel| reel.is_spinning()).collect();
+        assert_eq!(spinning, [false, false, true]);
+        assert_eq!(slot_machine.reels[0].position(), 3);
+        assert_eq!(slot_machine.reels[1].position(), 1);
+        assert!(slot_machine.holds.is_none());
+
+        // ホールドを使ったゲームのハズレでは、続けてホールドの権利は出ない
+        let Applied::Settled(result) = stop_reels_at(&mut slot_machine, 1000, &[3, 1, 1]) else {
+            panic!("最後のリールで精算されていない");
+        };
+        assert_eq!(result.payout, 0);
+        assert!(slot_machine.holds.is_none());
+    }
+
+    #[test]
+    fn nudges_are_counted_down_and_re_evaluate_the_line() {
+        let mut machine = classic();
+        machine.nudge.as_mut().unwrap().chance = 100;
+        machine.hold = None;
+        let count = machine.nudge.as_ref().unwrap().count;
+        let mut slot_machine = SlotMachine::new(machine, SessionRng::seeded(1), None);
+
+        // 👑👑⬜のハズレでナッジの権利が出る
+        slot_machine.apply(ReelCommand::StartAll, 0);
+        stop_reels_at(&mut slot_machine, 0, &[5, 5, 10]);
+        assert_eq!(slot_machine.nudges, count);
+        let balance = slot_machine.wallet.balance();
+
+        // 1コマ戻すと👑👑🔔（まだハズレ）
+        let Applied::Settled(result) = slot_machine.apply(ReelCommand::NudgeDown(2), 0) else {
+            panic!("ナッジで精算し直していない");
+        };
+        assert_eq!((result.stops, result.payout), (vec![5, 5, 9], 0));
+        assert_eq!(slot_machine.nudges, count - 1);
+
+        // もう1コマ戻すと👑👑👑が揃い、配当を受け取ってナッジは終わる
+        let Applied::Settled(result) = slot_machine.apply(ReelCommand::NudgeDown(2), 0) else {
+            panic!("ナッジで精算し直していない");
+        };
+        assert_eq!((result.stops, result.payout), (vec![5, 5, 8], 200));
+        assert_eq!((slot_machine.wallet.balance(), slot_machine.wallet.last_win()), (balance + 200, 200));
+        assert_eq!(slot_machine.nudges, 0);
+        assert!(matches!(slot_machine.apply(ReelCommand::NudgeUp(2), 0), Applied::Nothing));
+    }
+}
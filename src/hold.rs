@@ -0,0 +1,22 @@
+// === ホールド・ナッジモジュール ===
+// フルーツマシンのように、ハズレのあと次のゲームまでの間にプレイヤーがリールに手を加えられる権利
+// ホールド: 選んだリールを止めたまま、残りのリールだけを回す（2ゲーム続けてはホールドできない）
+// ナッジ: 止まっているリールを1コマずつ上下に動かす（もらった回数まで、当たりになった時点で終わり）
+// 権利の抽選はセッションの乱数で行うため、リプレイでも同じゲームで同じ権利が出る
+use rand::Rng;
+
+/// ホールドの定義
+pub struct HoldDef {
+    pub chance: u32,  // ハズレのあとにホールドできる確率（%）
+}
+
+/// ナッジの定義
+pub struct NudgeDef {
+    pub chance: u32,  // ハズレのあとにナッジがもらえる確率（%）
+    pub count: u32,   // もらえるナッジの回数
+}
+
+/// 百分率の確率で権利を抽選する
+pub fn draw(chance: u32, rng: &mut impl Rng) -> bool {
+    rng.gen_range(0..100) < chance
+}
@@ -0,0 +1,271 @@
+// === プログレッシブジャックポットモジュール ===
+// 毎ゲームのベットの一部を積み立て、ジャックポットの役が揃ったプレイヤーが積立額をすべて受け取る
+// 積立額はローカルのファイルに保存し、同じマシンで遊んでいる複数のセッションで共有する
+// ファイルには積立額と一緒に「何回当たったか（世代）」を保存する
+// 受け取るときは回転を始めた時点の世代をロックの中で照らし合わせるため、同時に揃っても受け取れるのは1つのセッションだけ
+use std::fs::{File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+// 積立ファイルの先頭行（書式のバージョン）
+const HEADER: &str = "gh-slot-jackpot 2";
+
+/// ジャックポットの定義
+pub struct JackpotDef {
+    pub entry: usize,       // ジャックポットの役（Paytable::entries の添字）
+    pub contribution: u32,  // ベットのうち積み立てる割合（%）
+    pub seed: u64,          // 当たった後（と最初）の積立額（クレジット）
+    pub pool: String,       // 積立ファイルの名前（同じ名前のマシン同士で共有する）
+}
+
+/// 積立ファイルから読んだ状態
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PoolState {
+    pub amount: u64,      // 積立額（クレジット、端数は切り捨て）
+    pub generation: u64,  // これまでに当たった回数（当たるたびに1増える）
+}
+
+/// 積立ファイルの中身（積立額は1/100クレジット単位）
+#[derive(Clone, Copy)]
+struct Pool {
+    hundredths: u64,  // 積立額（1/100クレジット単位）
+    generation: u64,  // これまでに当たった回数
+}
+
+impl Pool {
+    fn state(self) -> PoolState {
+        PoolState {
+            amount: self.hundredths / 100,
+            generation: self.generation,
+        }
+    }
+}
+
+/// 積立額を保存したファイル
+/// 端数を失わないよう、積立額は1/100クレジット単位で保存する
+pub struct JackpotPool {
+    path: PathBuf,   // 積立ファイルのパス
+    seed: u64,       // 当たった後の積立額（1/100クレジット単位）
+}
+
+impl JackpotPool {
+    /// マシンのジャックポット定義から積立ファイルを開く（ファイルは最初に書き込むときに作る）
+    /// 積立ファイルは一時ディレクトリに置く（同じコンピュータで同じ積立名を使う全ユーザーのセッションで共有）
+    pub fn new(def: &JackpotDef) -> Self {
+        Self {
+            path: std::env::temp_dir().join(format!("gh-slot-jackpot-{}.pool", def.pool)),
+            seed: def.seed * 100,
+        }
+    }
+
+    /// 現在の積立額と世代
+    /// 読むだけなので共有ロックで済ませる（まだファイルがなければ初期値）
+    pub fn state(&self) -> io::Result<PoolState> {
+        let mut file = match File::open(&self.path) {
+            Ok(file) => file,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(self.initial().state()),
+            Err(error) => return Err(error),
+        };
+        file.lock_shared()?;
+        let pool = read_pool(&mut file);
+        file.unlock()?;
+        Ok(pool?.unwrap_or(self.initial()).state())
+    }
+
+    /// ベットの一部を積み立てる
+    ///
+    /// # 引数
+    /// * `bet` - 1ゲームのベット額
+    /// * `contribution` - 積み立てる割合（%）
+    ///
+    /// # 戻り値
+    /// 積み立てた後の積立額と世代（この世代のうちに揃えば win で受け取れる）
+    pub fn contribute(&self, bet: u64, contribution: u32) -> io::Result<PoolState> {
+        // bet × contribution% をクレジットの1/100単位で表すと bet × contribution
+        self.update(|pool| {
+            let next = Pool {
+                hundredths: pool.hundredths + bet * contribution as u64,
+                ..pool
+            };
+            (next, next.state())
+        })
+    }
+
+    /// ジャックポットを受け取り、積立額を初期値に戻す
+    /// 読み出しと初期化を1回のロックの中で行い、世代が回転を始めた時点から変わっていれば受け取らない
+    /// （同時に揃った別のセッションが先に受け取った積立額を、初期値に戻った積立から二重に払わない）
+    ///
+    /// # 引数
+    /// * `generation` - 回転を始めた時点の世代（contribute・state で読んだもの）
+    ///
+    /// # 戻り値
+    /// 受け取ったクレジット（端数は次の積立に残す）、先に受け取られていればNone
+    pub fn win(&self, generation: u64) -> io::Result<Option<u64>> {
+        let seed = self.seed;
+        self.update(|pool| {
+            if pool.generation != generation {
+                return (pool, None);
+            }
+            let next = Pool {
+                hundredths: seed + pool.hundredths % 100,
+                generation: pool.generation + 1,
+            };
+            (next, Some(pool.hundredths / 100))
+        })
+    }
+
+    /// まだファイルがないときの積立
+    fn initial(&self) -> Pool {
+        Pool {
+            hundredths: self.seed,
+            generation: 0,
+        }
+    }
+
+    /// 排他ロックを取って積立を読み、書き換えてからロックを放す
+    ///
+    /// # 引数
+    /// * `change` - 今の積立を受け取り、(新しい積立, 呼び出し元に返す値) を返す
+    fn update<T>(&self, change: impl FnOnce(Pool) -> (Pool, T)) -> io::Result<T> {
+        let mut file = open_shared(&self.path)?;
+        file.lock()?;
+        let pool = read_pool(&mut file)?.unwrap_or(self.initial());
+        let (next, value) = change(pool);
+        file.set_len(0)?;
+        file.seek(SeekFrom::Start(0))?;
+        writeln!(file, "{}", HEADER)?;
+        writeln!(file, "{}", next.hundredths)?;
+        writeln!(file, "{}", next.generation)?;
+        file.sync_all()?;
+        file.unlock()?;
+        Ok(value)
+    }
+}
+
+/// 積立ファイルを読み書きできるように開く（なければ作る）
+/// 別のユーザーのセッションも同じ積立を使えるよう、作るときは umask に関係なく全員が読み書きできる権限にする
+fn open_shared(path: &Path) -> io::Result<File> {
+    match OpenOptions::new().read(true).write(true).create_new(true).open(path) {
+        Ok(file) => {
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::PermissionsExt;
+                file.set_permissions(std::fs::Permissions::from_mode(0o666))?;
+            }
+            Ok(file)
+        }
+        Err(error) if error.kind() == io::ErrorKind::AlreadyExists => {
+            OpenOptions::new().read(true).write(true).open(path)
+        }
+        Err(error) => Err(error),
+    }
+}
+
+/// 積立ファイルの内容を読む（空のファイルはNone）
+fn read_pool(file: &mut File) -> io::Result<Option<Pool>> {
+    let mut text = String::new();
+    file.read_to_string(&mut text)?;
+    if text.trim().is_empty() {
+        return Ok(None);
+    }
+    let invalid = || io::Error::new(io::ErrorKind::InvalidData, "ジャックポットの積立ファイルの書式が不正です");
+    let mut lines = text.lines();
+    if lines.next() != Some(HEADER) {
+        return Err(invalid());
+    }
+    let mut number = || lines.next().and_then(|line| line.trim().parse().ok()).ok_or_else(invalid);
+    let hundredths = number()?;
+    let generation = number()?;
+    Ok(Some(Pool { hundredths, generation }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Barrier};
+    use std::thread;
+
+    /// テストごとに別の積立ファイルを使う（終わったら消す）
+    fn pool(name: &str) -> JackpotPool {
+        let pool = JackpotPool::new(&JackpotDef {
+            entry: 0,
+            contribution: 10,
+            seed: 500,
+            pool: format!("test-{}-{}", name, std::process::id()),
+        });
+        let _ = std::fs::remove_file(&pool.path);
+        pool
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn new_pool_files_are_writable_by_every_user() {
+        use std::os::unix::fs::PermissionsExt;
+        let pool = pool("mode");
+        pool.contribute(100, 10).unwrap();
+        let mode = std::fs::metadata(&pool.path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o666);
+        std::fs::remove_file(&pool.path).unwrap();
+    }
+
+    #[test]
+    fn only_one_of_two_simultaneous_hits_is_paid() {
+        let pool = Arc::new(pool("race-win"));
+        for round in 0..20 {
+            // 2つのセッションが同じ世代で回し始め、同時に揃えた
+            let generation = pool.contribute(100, 10).unwrap().generation;
+            assert_eq!(generation, round);
+            let expected = pool.state().unwrap().amount;
+            let barrier = Arc::new(Barrier::new(2));
+            let handles: Vec<_> = (0..2)
+                .map(|_| {
+                    let (pool, barrier) = (Arc::clone(&pool), Arc::clone(&barrier));
+                    thread::spawn(move || {
+                        barrier.wait();
+                        pool.win(generation).unwrap()
+                    })
+                })
+                .collect();
+            let mut paid: Vec<Option<u64>> = handles.into_iter().map(|handle| handle.join().unwrap()).collect();
+            paid.sort();
+            assert_eq!(paid, vec![None, Some(expected)]);
+            assert_eq!(pool.state().unwrap(), PoolState { amount: 500, generation: round + 1 });
+        }
+        std::fs::remove_file(&pool.path).unwrap();
+    }
+
+    #[test]
+    fn contributions_racing_a_win_are_never_lost() {
+        let pool = Arc::new(pool("race-contribute"));
+        let generation = pool.state().unwrap().generation;
+        let barrier = Arc::new(Barrier::new(5));
+        // 4つのセッションが100回ずつ積み立てる間に、1つのセッションが当たる
+        let contributors: Vec<_> = (0..4)
+            .map(|_| {
+                let (pool, barrier) = (Arc::clone(&pool), Arc::clone(&barrier));
+                thread::spawn(move || {
+                    barrier.wait();
+                    for _ in 0..100 {
+                        pool.contribute(10, 10).unwrap();
+                    }
+                })
+            })
+            .collect();
+        let winner = {
+            let (pool, barrier) = (Arc::clone(&pool), Arc::clone(&barrier));
+            thread::spawn(move || {
+                barrier.wait();
+                pool.win(generation).unwrap()
+            })
+        };
+        for handle in contributors {
+            handle.join().unwrap();
+        }
+        let won = winner.join().unwrap().expect("世代が変わる前の当たりは受け取れる");
+        // 1回の積立は 10 × 10% = 1クレジット。当たる前と後の積立を合わせると初期値 + 400
+        let state = pool.state().unwrap();
+        assert_eq!(state.generation, generation + 1);
+        assert_eq!(won + state.amount, 500 + 500 + 400);
+        std::fs::remove_file(&pool.path).unwrap();
+    }
+}
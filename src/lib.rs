@@ -0,0 +1,60 @@
+// === gh-slot ライブラリ ===
+// スロットマシンのエンジン（リール・当選判定・クレジット・ゲーム進行）を画面に依存しない形で提供する
+// ターミナル版（gh slot）はこのライブラリを使うフロントエンドの1つ（描画とキー入力だけを受け持つ）
+//
+// 別のフロントエンドからは次の流れで使う:
+// 1. SlotMachine::new でマシン定義・乱数・設定からゲームを作る
+// 2. 操作を ReelCommand にして apply に時刻とともに渡す（精算すると Applied::Settled が返る）
+// 3. 描画の間隔ごとに tick でスナップショットを取り直し、snapshot を Renderer の render に渡して画面を描く
+
+// リールモジュール（reel.rs）
+pub mod reel;
+// マシン定義モジュール（machine.rs）
+pub mod machine;
+// ヘッドレスシミュレーションモジュール（simulate.rs）
+pub mod simulate;
+// パーシート生成モジュール（parsheet.rs）
+pub mod parsheet;
+// 配当表モジュール（paytable.rs）
+pub mod paytable;
+// クレジット管理モジュール（wallet.rs）
+pub mod wallet;
+// 乱数生成モジュール（rng.rs）
+pub mod rng;
+// 操作コマンドモジュール（command.rs）
+pub mod command;
+// セッションログモジュール（session.rs）
+pub mod session;
+// 内部抽選モジュール（lottery.rs）
+pub mod lottery;
+// 設定（1〜6）モジュール（setting.rs）
+pub mod setting;
+// ボーナスゲームモジュール（bonus.rs）
+pub mod bonus;
+// スキャッタ・フリースピンモジュール（scatter.rs）
+pub mod scatter;
+// カスケード（連鎖）モジュール（cascade.rs）
+pub mod cascade;
+// ホールド・ナッジモジュール（hold.rs）
+pub mod hold;
+// ダブルアップモジュール（gamble.rs）
+pub mod gamble;
+// プログレッシブジャックポットモジュール（jackpot.rs）
+pub mod jackpot;
+// 時計モジュール（clock.rs）
+pub mod clock;
+// ゲームループの時計モジュール（ticker.rs）
+pub mod ticker;
+// ゲーム進行モジュール（game.rs）
+pub mod game;
+// 画面描画モジュール（render.rs）
+pub mod render;
+// 文字の格子バックエンド（text_grid.rs）
+pub mod text_grid;
+
+// よく使う型はクレートの直下からも使えるようにする
+pub use command::ReelCommand;
+pub use game::{Applied, SlotMachine, Snapshot};
+pub use render::{Renderer, View};
+pub use machine::MachineDef;
+pub use rng::SessionRng;
@@ -0,0 +1,153 @@
+// === 内部抽選モジュール ===
+// 回転開始時に抽選テーブルから当選役（またはハズレ）を先に決め、
+// リールはその結果と矛盾しない位置に止める（パチスロ・規制機の方式、停止位置の選び方は reel::SlipControl）
+// マシン定義に [[lottery]] がある場合のみ有効になる
+use rand::Rng;
+
+use crate::machine::{MachineDef, SymbolId};
+use crate::reel::{evaluate, visible_symbols, LineWin};
+
+/// 抽選で決まる1ゲームの結果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    /// ハズレ（有効ラインに当選なし）
+    Miss,
+    /// 当選役（有効ラインにはこの役だけが揃う）
+    Win { symbol: SymbolId, count: usize },
+}
+
+impl Outcome {
+    /// 判定結果がこの抽選結果と一致するか
+    pub fn matches(&self, wins: &[LineWin]) -> bool {
+        match *self {
+            Outcome::Miss => wins.is_empty(),
+            Outcome::Win { symbol, count } => {
+                !wins.is_empty() && wins.iter().all(|win| win.symbol == symbol && win.count == count)
+            }
+        }
+    }
+}
+
+/// 抽選テーブルの1行（結果と重み）
+#[derive(Debug, Clone, Copy)]
+pub struct LotteryEntry {
+    pub outcome: Outcome,
+    pub weight: u32,  // 当選確率 = 重み ÷ 重みの合計
+}
+
+/// 抽選テーブル
+pub struct Lottery {
+    entries: Vec<LotteryEntry>,
+    total_weight: u64,  // 重みの合計
+}
+
+impl Lottery {
+    /// 抽選テーブルの行から作成（重みの合計は1以上であること）
+    pub fn new(entries: Vec<LotteryEntry>) -> Self {
+        let total_weight = entries.iter().map(|entry| entry.weight as u64).sum();
+        Self { entries, total_weight }
+    }
+
+    /// 抽選テーブルの全行
+    pub fn entries(&self) -> &[LotteryEntry] {
+        &self.entries
+    }
+
+    /// 結果の当選確率（全ライン有効時）
+    pub fn probability(&self, entry: &LotteryEntry) -> f64 {
+        entry.weight as f64 / self.total_weight as f64
+    }
+
+    /// 重みに従って1ゲームの結果を抽選する
+    /// テーブルは全ライン有効時の確率とし、有効ラインが少ないときは
+    /// 当選確率を「有効ライン数 ÷ 全ライン数」倍に下げる（1ラインあたりのRTPを揃えるため）
+    ///
+    /// # 引数
+    /// * `lines` - 有効ライン数
+    /// * `max_lines` - 全ライン数
+    pub fn draw(&self, rng: &mut impl Rng, lines: usize, max_lines: usize) -> Outcome {
+        let mut ticket = rng.gen_range(0..self.total_weight);
+        let mut outcome = Outcome::Miss;
+        for entry in &self.entries {
+            if ticket < entry.weight as u64 {
+                outcome = entry.outcome;
+                break;
+            }
+            ticket -= entry.weight as u64;
+        }
+        // 有効ラインが少ないときは一部の当選をハズレに置き換える
+        if outcome != Outcome::Miss && rng.gen_range(0..max_lines) >= lines {
+            return Outcome::Miss;
+        }
+        outcome
+    }
+}
+
+/// 停止済みのリールを固定したまま、残りのリールで抽選結果を成立させられるか
+///
+/// # 引数
+/// * `stops` - 各リールの停止位置（未停止のリールはNone）
+pub fn reachable(machine: &MachineDef, paylines: &[Vec<usize>], outcome: Outcome, stops: &[Option<usize>]) -> bool {
+    let mut window: Vec<Vec<SymbolId>> = vec![Vec::new(); machine.reels.len()];
+    search(machine, paylines, outcome, stops, 0, &mut window)
+}
+
+/// 左のリールから順に停止位置を当てはめる深さ優先探索（見つかった時点で打ち切る）
+fn search(
+    machine: &MachineDef,
+    paylines: &[Vec<usize>],
+    outcome: Outcome,
+    stops: &[Option<usize>],
+    reel: usize,
+    window: &mut Vec<Vec<SymbolId>>,
+) -> bool {
+    if reel == machine.reels.len() {
+        let wins = evaluate(machine, window, paylines, 1);
+        return outcome.matches(&wins);
+    }
+    let strip = &machine.reels[reel];
+    let candidates = match stops[reel] {
+        Some(stop) => stop..stop + 1,
+        None => 0..strip.len(),
+    };
+    for stop in candidates {
+        window[reel] = visible_symbols(strip, stop, machine.rows);
+        if search(machine, paylines, outcome, stops, reel + 1, window) {
+            return true;
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::SessionRng;
+
+    #[test]
+    fn win_chances_scale_with_active_lines_and_miss_takes_the_rest() {
+        // 全ライン（7ライン）有効時はハズレ50%・役A 30%・役B 20%
+        let (a, b) = (Outcome::Win { symbol: 0, count: 3 }, Outcome::Win { symbol: 1, count: 3 });
+        let lottery = Lottery::new(vec![
+            LotteryEntry { outcome: Outcome::Miss, weight: 50 },
+            LotteryEntry { outcome: a, weight: 30 },
+            LotteryEntry { outcome: b, weight: 20 },
+        ]);
+        let mut rng = SessionRng::seeded(9);
+        let draws = 200_000;
+        for lines in [1, 3, 5, 7] {
+            let mut counts = [0u32; 3];
+            for _ in 0..draws {
+                let drawn = lottery.draw(&mut rng, lines, 7);
+                counts[[Outcome::Miss, a, b].iter().position(|&outcome| outcome == drawn).unwrap()] += 1;
+            }
+            // 当選確率は「有効ライン数 ÷ 全ライン数」倍になり、減った分はハズレになる
+            let ratio = lines as f64 / 7.0;
+            let expected = [1.0 - 0.5 * ratio, 0.3 * ratio, 0.2 * ratio];
+            for (count, expected) in counts.iter().zip(expected) {
+                let observed = *count as f64 / draws as f64;
+                assert!((observed - expected).abs() < 0.005, "{}ライン: {} / 期待 {}", lines, observed, expected);
+            }
+        }
+    }
+}
@@ -0,0 +1,1139 @@
+// === マシン定義モジュール ===
+// リール配列・シンボル・ペイライン・配当表・回転速度をTOML/JSONファイルから読み込む
+// 再コンパイルせずに新しいマシンを設計・配布できるようにするための仕組み
+use serde::Deserialize;               // 定義ファイルの読み込み（デシリアライズ）用
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::bonus::{BonusDef, BonusKind};
+use crate::lottery::{self, Lottery, LotteryEntry, Outcome};
+use crate::paytable::{PayEntry, PayRule, Paytable, SymbolGroup};
+use crate::reel::{Evaluation, PaylineSet, SlipControl};
+use crate::scatter::FreeSpinsDef;
+use crate::cascade::CascadeDef;
+use crate::hold::{HoldDef, NudgeDef};
+use crate::gamble::GambleDef;
+use crate::jackpot::JackpotDef;
+use crate::setting::SETTINGS;
+
+// 組み込みの標準マシン定義（--machine 未指定時に使用）
+const DEFAULT_MACHINE: &str = include_str!("../machines/default.toml");
+
+// 定義できるリール数の上限（停止キーに数字キー1〜9を割り当てるため）
+pub const MAX_REELS: usize = 9;
+
+// 最大すべりコマ数の既定値（パチスロの規定と同じ4コマ）
+const DEFAULT_MAX_SLIP: usize = 4;
+
+// ダブルアップで続けて倍にできる回数の上限（配当が桁あふれしないように）
+const MAX_GAMBLE_LADDER: u32 = 10;
+
+/// シンボルの識別番号（MachineDef::symbols の添字）
+pub type SymbolId = usize;
+
+/// マシン定義の読み込み・検証エラー
+#[derive(Debug)]
+pub enum MachineError {
+    /// ファイルを読み込めなかった
+    Io { path: PathBuf, source: io::Error },
+    /// TOML/JSONとして解釈できなかった
+    Parse { path: PathBuf, message: String },
+    /// 内容に矛盾がある（どの項目が何故おかしいかを保持）
+    Invalid { field: String, reason: String },
+}
+
+impl fmt::Display for MachineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MachineError::Io { path, source } => {
+                write!(f, "マシン定義を読み込めません: {}: {}", path.display(), source)
+            }
+            MachineError::Parse { path, message } => {
+                write!(f, "マシン定義の書式が不正です: {}: {}", path.display(), message)
+            }
+            MachineError::Invalid { field, reason } => {
+                write!(f, "マシン定義の内容が不正です: {}: {}", field, reason)
+            }
+        }
+    }
+}
+
+impl std::error::Error for MachineError {}
+
+/// 検証エラーを作るための短縮関数
+fn invalid(field: impl Into<String>, reason: impl Into<String>) -> MachineError {
+    MachineError::Invalid {
+        field: field.into(),
+        reason: reason.into(),
+    }
+}
+
+// === 定義ファイルの書式（ファイルの見た目そのまま） ===
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct MachineFile {
+    name: String,
+    rows: usize,
+    evaluation: Option<String>,     // "lines"（ペイライン）または "ways"（ウェイ）、省略時は "lines"
+    #[serde(default)]
+    line_options: Vec<usize>,       // ウェイ判定のマシンでは省略する
+    reels: Vec<Vec<String>>,
+    #[serde(default)]
+    paylines: Vec<Vec<usize>>,      // ウェイ判定のマシンでは省略する
+    timing: TimingFile,
+    symbols: Vec<SymbolFile>,
+    paytable: Vec<PayFile>,
+    #[serde(default)]
+    groups: Vec<GroupFile>,     // 省略時はシンボルのグループなし
+    #[serde(default)]
+    lottery: Vec<LotteryFile>,  // 省略時は内部抽選なし（押したタイミングで停止位置が決まる）
+    slip: Option<SlipFile>,     // 省略時は最大4コマ・すべりの少ない順（内部抽選のマシンのみ）
+    #[serde(default)]
+    bonus: Vec<BonusFile>,      // 省略時はボーナスなし（内部抽選のマシンのみ）
+    free_spins: Option<FreeSpinsFile>, // 省略時はフリースピンなし
+    cascade: Option<CascadeFile>,      // 省略時は連鎖なし
+    hold: Option<HoldFile>,            // 省略時はホールドなし
+    nudge: Option<NudgeFile>,          // 省略時はナッジなし
+    gamble: Option<GambleFile>,        // 省略時はダブルアップなし
+    jackpot: Option<JackpotFile>,      // 省略時はジャックポットなし
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TimingFile {
+    spin_step_ms: u64,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SymbolFile {
+    glyph: String,
+    name: String,
+    #[serde(default)]
+    wild: bool,                    // 省略時は通常のシンボル
+    wild_multiplier: Option<u64>,  // ワイルドが代わりになったときの配当倍率（省略時は1倍）
+    #[serde(default)]
+    scatter: bool,                 // スキャッタ（ペイラインに関係なく盤面のどこでも数える）かどうか
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PayFile {
+    symbol: Option<String>,     // 対象シンボル（group とどちらか一方を指定）
+    group: Option<String>,      // 対象のグループ名
+    count: usize,
+    rule: Option<String>,       // "left"（左から続けて揃う）または "anywhere"（ライン上のどこでも）、省略時は "left"
+    multiplier: u64,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct GroupFile {
+    name: String,
+    symbols: Vec<String>,       // グループに含めるシンボル
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct LotteryFile {
+    symbol: Option<String>,     // 省略するとハズレ
+    count: Option<usize>,
+    weight: Option<u32>,        // 全設定共通の重み
+    weights: Option<Vec<u32>>,  // 設定1〜6ごとの重み（weight の代わりに指定）
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct BonusFile {
+    kind: String,               // "big" または "reg"
+    symbol: String,             // 揃うとボーナスが始まるシンボル
+    count: usize,
+    games: u32,                 // ボーナスのゲーム数
+    payout_cap: Option<u64>,    // 払い出しの上限（1枚掛け換算、省略時はゲーム数のみで終了）
+    lottery: Vec<LotteryFile>,  // ボーナス中の抽選テーブル
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FreeSpinsFile {
+    symbol: String,  // 対象のスキャッタ
+    count: usize,    // 始まるのに必要な数
+    spins: u32,      // 与えられる回数
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CascadeFile {
+    multipliers: Vec<u64>,  // 判定ごとの配当倍率（停止時の盤面から順に）
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct HoldFile {
+    chance: u32,  // ハズレのあとにホールドできる確率（%）
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct NudgeFile {
+    chance: u32,  // ハズレのあとにナッジがもらえる確率（%）
+    count: u32,   // もらえるナッジの回数
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct GambleFile {
+    ladder: u32,  // 続けて倍にできる回数
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct JackpotFile {
+    symbol: String,     // ジャックポットの役のシンボル
+    count: usize,       // 揃う数
+    contribution: u32,  // ベットのうち積み立てる割合（%）
+    seed: u64,          // 積立額の初期値（クレジット）
+    pool: String,       // 積立ファイルの名前（同じ名前のマシン同士で共有する）
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SlipFile {
+    max: usize,
+    #[serde(default)]
+    control: Vec<ControlFile>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ControlFile {
+    symbol: Option<String>,  // 省略するとハズレ用のテーブル
+    count: Option<usize>,
+    order: Vec<usize>,       // 試すすべりコマ数の優先順
+}
+
+// === 検証済みのマシン定義 ===
+
+/// シンボルのメタデータ
+#[derive(Debug, Clone)]
+pub struct SymbolDef {
+    pub glyph: String,         // 画面に表示する絵文字
+    pub name: String,          // シンボルの名前（配当表などで使用）
+    pub wild: bool,            // ワイルド（他のシンボルの代わりになる）かどうか
+    pub wild_multiplier: u64,  // ワイルドが代わりになったときの配当倍率（通常のシンボルは1）
+    pub scatter: bool,         // スキャッタ（盤面のどこでも数え、総ベットの倍数を払う）かどうか
+}
+
+/// 検証済みのマシン定義
+/// リールと当選判定はすべてこの定義に従って動作する
+pub struct MachineDef {
+    pub name: String,               // マシン名
+    pub symbols: Vec<SymbolDef>,    // シンボル一覧（SymbolIdの添字順）
+    pub reels: Vec<Vec<SymbolId>>,  // 各リールのシンボル配列（左→右）
+    pub rows: usize,                // 画面に表示される縦のシンボル数
+    pub evaluation: Evaluation,     // 当選判定の方式（ライン / ウェイ）
+    pub paylines: PaylineSet,       // 全ペイライン（ウェイ判定のマシンでは空）
+    pub line_options: Vec<usize>,   // 選択できる有効ライン数（昇順、ウェイ判定のマシンは [1]）
+    pub paytable: Paytable,         // 配当表
+    pub spin_step: Duration,        // リールが1コマ進む間隔
+    lotteries: Vec<Lottery>,        // 内部抽選テーブル（なし / 設定共通の1つ / 設定1〜6の6つ）
+    pub slip: SlipControl,          // 停止制御（抽選モードのマシンのみ使う）
+    pub bonuses: Vec<BonusDef>,     // BIG・REGボーナス（抽選モードのマシンのみ）
+    pub free_spins: Option<FreeSpinsDef>, // フリースピン（スキャッタのあるマシンのみ）
+    pub cascade: Option<CascadeDef>,      // カスケード（当選したシンボルを消して連鎖させるマシンのみ）
+    pub hold: Option<HoldDef>,            // ホールド（ハズレのあと、次のゲームでリールを止めておける）
+    pub nudge: Option<NudgeDef>,          // ナッジ（ハズレのあと、止まったリールを1コマずつ動かせる）
+    pub gamble: Option<GambleDef>,        // ダブルアップ（当たりのあと、獲得したクレジットを賭けられる）
+    pub jackpot: Option<JackpotDef>,      // プログレッシブジャックポット（積立額を同じマシンのセッションで共有）
+}
+
+impl MachineDef {
+    /// 組み込みの標準マシン定義を読み込む
+    pub fn builtin() -> Self {
+        Self::from_toml(DEFAULT_MACHINE, Path::new("machines/default.toml"))
+            .expect("組み込みのマシン定義が不正です")
+    }
+
+    /// 定義ファイルを読み込んで検証する
+    /// 拡張子が .json ならJSON、それ以外はTOMLとして解釈する
+    pub fn load(path: &Path) -> Result<Self, MachineError> {
+        let source = fs::read_to_string(path).map_err(|source| MachineError::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        let is_json = path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("json"));
+        if is_json {
+            Self::from_json(&source, path)
+        } else {
+            Self::from_toml(&source, path)
+        }
+    }
+
+    /// TOML文字列から読み込む（pathはエラーメッセージ用）
+    pub fn from_toml(source: &str, path: &Path) -> Result<Self, MachineError> {
+        let file: MachineFile = toml::from_str(source).map_err(|err| MachineError::Parse {
+            path: path.to_path_buf(),
+            message: err.to_string(),
+        })?;
+        Self::validate(file)
+    }
+
+    /// JSON文字列から読み込む（pathはエラーメッセージ用）
+    pub fn from_json(source: &str, path: &Path) -> Result<Self, MachineError> {
+        let file: MachineFile = serde_json::from_str(source).map_err(|err| MachineError::Parse {
+            path: path.to_path_buf(),
+            message: err.to_string(),
+        })?;
+        Self::validate(file)
+    }
+
+    /// 指定した設定の内部抽選テーブル（内部抽選のないマシンはNone）
+    /// 設定のないマシンは設定に関係なく共通のテーブルを返す
+    ///
+    /// # 引数
+    /// * `setting` - 設定（1〜6）
+    pub fn lottery(&self, setting: usize) -> Option<&Lottery> {
+        match self.lotteries.len() {
+            1 => self.lotteries.first(),
+            _ => self.lotteries.get(setting.wrapping_sub(1)),
+        }
+    }
+
+    /// 設定1〜6を持つマシンかどうか
+    pub fn has_settings(&self) -> bool {
+        self.lotteries.len() > 1
+    }
+
+    /// 内部抽選のあるマシンかどうか
+    pub fn has_lottery(&self) -> bool {
+        !self.lotteries.is_empty()
+    }
+
+    /// 全ライン数（ウェイ判定のマシンは1ライン分のベットとして1）
+    pub fn max_lines(&self) -> usize {
+        match self.evaluation {
+            Evaluation::Lines => self.paylines.all().len(),
+            Evaluation::Ways => 1,
+        }
+    }
+
+    /// ウェイ判定で盤面がとりうるウェイの総数（各リールの段数の積、5×3なら243）
+    pub fn total_ways(&self) -> u64 {
+        (self.rows as u64).pow(self.reels.len() as u32)
+    }
+
+    /// シンボルの表示用絵文字を取得
+    pub fn glyph(&self, symbol: SymbolId) -> &str {
+        &self.symbols[symbol].glyph
+    }
+
+    /// 配当表の行の表示用の名前（グループの行はグループ名、それ以外はシンボルの名前）
+    pub fn pay_name(&self, entry: &PayEntry) -> &str {
+        match entry.group {
+            Some(group) => &self.paytable.groups()[group].name,
+            None => &self.symbols[entry.symbol].name,
+        }
+    }
+
+    /// 配当表の行の表示（例: 「🍒 🍒 🍒」「🍒×2（左から）」「［フルーツ］×3（ライン上どこでも）」）
+    /// ライン全体にシンボルが揃う行は、これまでどおりシンボルを並べて表示する
+    pub fn pay_label(&self, entry: &PayEntry) -> String {
+        let unit = match entry.group {
+            Some(group) => format!("［{}］", self.paytable.groups()[group].name),
+            None => self.glyph(entry.symbol).to_string(),
+        };
+        if self.symbols[entry.symbol].scatter {
+            return format!("{} どこでも{}個", unit, entry.count);
+        }
+        match entry.rule {
+            PayRule::Left if entry.count == self.reels.len() && entry.group.is_none() => {
+                vec![unit.as_str(); entry.count].join(" ")
+            }
+            PayRule::Left if entry.count == self.reels.len() => format!("{}×{}", unit, entry.count),
+            PayRule::Left => format!("{}×{}（左から）", unit, entry.count),
+            PayRule::Anywhere => format!("{}×{}（ライン上どこでも）", unit, entry.count),
+        }
+    }
+
+    /// 抽選結果の表示（当選役は配当表の行の表示、ハズレは「ハズレ」）
+    pub fn outcome_label(&self, outcome: Outcome) -> String {
+        let Outcome::Win { symbol, count } = outcome else {
+            return "ハズレ".to_string();
+        };
+        self.paytable
+            .entries()
+            .iter()
+            .find(|entry| entry.symbol == symbol && entry.group.is_none() && entry.count == count)
+            .map(|entry| self.pay_label(entry))
+            .unwrap_or_else(|| vec![self.glyph(symbol); count].join(" "))
+    }
+
+    /// ファイルの内容を検証し、シンボルを識別番号に変換する
+    fn validate(file: MachineFile) -> Result<Self, MachineError> {
+        // --- シンボル ---
+        if file.symbols.is_empty() {
+            return Err(invalid("symbols", "シンボルが1つも定義されていません"));
+        }
+        let mut ids: HashMap<&str, SymbolId> = HashMap::new();
+        for (i, symbol) in file.symbols.iter().enumerate() {
+            if symbol.glyph.is_empty() {
+                return Err(invalid(format!("symbols[{}].glyph", i), "空文字は使えません"));
+            }
+            if ids.insert(&symbol.glyph, i).is_some() {
+                return Err(invalid(
+                    format!("symbols[{}].glyph", i),
+                    format!("シンボル \"{}\" が重複しています", symbol.glyph),
+                ));
+            }
+            match symbol.wild_multiplier {
+                Some(_) if !symbol.wild => {
+                    return Err(invalid(
+                        format!("symbols[{}].wild_multiplier", i),
+                        "ワイルド（wild = true）のシンボルにのみ指定できます",
+                    ));
+                }
+                Some(0) => {
+                    return Err(invalid(format!("symbols[{}].wild_multiplier", i), "1以上の値が必要です"));
+                }
+                _ => {}
+            }
+            if symbol.wild && symbol.scatter {
+                return Err(invalid(format!("symbols[{}]", i), "ワイルドとスキャッタは同時に指定できません"));
+            }
+        }
+        let lookup = |field: String, glyph: &str| {
+            ids.get(glyph)
+                .copied()
+                .ok_or_else(|| invalid(field, format!("未定義のシンボル \"{}\" です", glyph)))
+        };
+
+        // --- 盤面サイズ ---
+        // リールの停止キーは数字キー1〜9のため、リール数は最大9
+        if file.reels.is_empty() || file.reels.len() > MAX_REELS {
+            return Err(invalid(
+                "reels",
+                format!("リール数は1〜{}で指定してください（指定: {}）", MAX_REELS, file.reels.len()),
+            ));
+        }
+        if file.rows == 0 {
+            return Err(invalid("rows", "表示段数は1以上で指定してください"));
+        }
+
+        // --- リール配列 ---
+        let mut reels = Vec::with_capacity(file.reels.len());
+        for (r, strip) in file.reels.iter().enumerate() {
+            if strip.len() < file.rows {
+                return Err(invalid(
+                    format!("reels[{}]", r),
+                    format!("シンボル数が表示段数（{}）より少ないです", file.rows),
+                ));
+            }
+            let strip = strip
+                .iter()
+                .enumerate()
+                .map(|(i, glyph)| lookup(format!("reels[{}][{}]", r, i), glyph))
+                .collect::<Result<Vec<_>, _>>()?;
+            reels.push(strip);
+        }
+
+        // --- 判定方式 ---
+        let evaluation = match file.evaluation.as_deref() {
+            None | Some("lines") => Evaluation::Lines,
+            Some("ways") => Evaluation::Ways,
+            Some(other) => {
+                return Err(invalid(
+                    "evaluation",
+                    format!("\"{}\" は使えません（lines または ways）", other),
+                ));
+            }
+        };
+
+        // --- ペイライン ---
+        // ウェイ判定ではペイラインを使わず、ベットは1ライン分（掛け枚数 × 1）とする
+        let line_options = match evaluation {
+            Evaluation::Ways => {
+                if !file.paylines.is_empty() {
+                    return Err(invalid("paylines", "ウェイ判定（evaluation = \"ways\"）のマシンでは指定できません"));
+                }
+                if !file.line_options.is_empty() {
+                    return Err(invalid("line_options", "ウェイ判定（evaluation = \"ways\"）のマシンでは指定できません"));
+                }
+                vec![1]
+            }
+            Evaluation::Lines => {
+                if file.paylines.is_empty() {
+                    return Err(invalid("paylines", "ペイラインが1本も定義されていません"));
+                }
+                for (i, line) in file.paylines.iter().enumerate() {
+                    if line.len() != reels.len() {
+                        return Err(invalid(
+                            format!("paylines[{}]", i),
+                            format!("リール数（{}）と同じ長さが必要です", reels.len()),
+                        ));
+                    }
+                    if let Some(&row) = line.iter().find(|&&row| row >= file.rows) {
+                        return Err(invalid(
+                            format!("paylines[{}]", i),
+                            format!("段 {} は表示範囲（0〜{}）外です", row, file.rows - 1),
+                        ));
+                    }
+                }
+
+                // 有効ライン数の選択肢
+                if file.line_options.is_empty() {
+                    return Err(invalid("line_options", "選択肢が1つも定義されていません"));
+                }
+                if file.line_options.windows(2).any(|pair| pair[0] >= pair[1]) {
+                    return Err(invalid("line_options", "昇順（重複なし）で指定してください"));
+                }
+                if let Some(&lines) = file
+                    .line_options
+                    .iter()
+                    .find(|&&lines| lines == 0 || lines > file.paylines.len())
+                {
+                    return Err(invalid(
+                        "line_options",
+                        format!("{} ライン は1〜{}の範囲外です", lines, file.paylines.len()),
+                    ));
+                }
+                file.line_options
+            }
+        };
+
+        // --- シンボルのグループ ---
+        let mut groups: Vec<SymbolGroup> = Vec::with_capacity(file.groups.len());
+        for (i, group) in file.groups.iter().enumerate() {
+            if group.name.is_empty() {
+                return Err(invalid(format!("groups[{}].name", i), "空文字は使えません"));
+            }
+            if groups.iter().any(|other| other.name == group.name) {
+                return Err(invalid(
+                    format!("groups[{}].name", i),
+                    format!("グループ \"{}\" が重複しています", group.name),
+                ));
+            }
+            if group.symbols.is_empty() {
+                return Err(invalid(format!("groups[{}].symbols", i), "シンボルが1つも指定されていません"));
+            }
+            let mut members = Vec::with_capacity(group.symbols.len());
+            for (j, glyph) in group.symbols.iter().enumerate() {
+                let field = format!("groups[{}].symbols[{}]", i, j);
+                let symbol = lookup(field.clone(), glyph)?;
+                // ワイルドはもともとどのシンボルの代わりにもなり、スキャッタはライン上で揃わないため含めない
+                if file.symbols[symbol].wild || file.symbols[symbol].scatter {
+                    return Err(invalid(field, "ワイルドとスキャッタはグループに含められません"));
+                }
+                if members.contains(&symbol) {
+                    return Err(invalid(field, format!("シンボル \"{}\" が重複しています", glyph)));
+                }
+                members.push(symbol);
+            }
+            groups.push(SymbolGroup {
+                name: group.name.clone(),
+                symbols: members,
+            });
+        }
+
+        // --- 配当表 ---
+        // スキャッタは盤面のどこでも数えるため、盤面のマス数まで指定できる
+        let cells = reels.len() * file.rows;
+        let mut entries: Vec<PayEntry> = Vec::with_capacity(file.paytable.len());
+        for (i, pay) in file.paytable.iter().enumerate() {
+            let (symbol, group, target) = match (&pay.symbol, &pay.group) {
+                (Some(glyph), None) => (lookup(format!("paytable[{}].symbol", i), glyph)?, None, glyph),
+                (None, Some(name)) => {
+                    let group = groups.iter().position(|group| &group.name == name).ok_or_else(|| {
+                        invalid(format!("paytable[{}].group", i), format!("未定義のグループ \"{}\" です", name))
+                    })?;
+                    (groups[group].symbols[0], Some(group), name)
+                }
+                _ => {
+                    return Err(invalid(
+                        format!("paytable[{}]", i),
+                        "symbol と group はどちらか一方を指定してください",
+                    ));
+                }
+            };
+            let rule = match pay.rule.as_deref() {
+                None | Some("left") => PayRule::Left,
+                Some("anywhere") => PayRule::Anywhere,
+                Some(other) => {
+                    return Err(invalid(
+                        format!("paytable[{}].rule", i),
+                        format!("\"{}\" は使えません（left または anywhere）", other),
+                    ));
+                }
+            };
+            let scatter = file.symbols[symbol].scatter;
+            // スキャッタは盤面全体で数えるため、揃い方は指定できない
+            if scatter && pay.rule.is_some() {
+                return Err(invalid(format!("paytable[{}].rule", i), "スキャッタの行には指定できません"));
+            }
+            // ウェイは左のリールから続けて揃うもののみ
+            if evaluation == Evaluation::Ways && rule == PayRule::Anywhere {
+                return Err(invalid(
+                    format!("paytable[{}].rule", i),
+                    "ウェイ判定（evaluation = \"ways\"）のマシンでは anywhere は使えません",
+                ));
+            }
+            let max_count = if scatter { cells } else { reels.len() };
+            if pay.count == 0 || pay.count > max_count {
+                return Err(invalid(
+                    format!("paytable[{}].count", i),
+                    format!("揃う数は1〜{}で指定してください", max_count),
+                ));
+            }
+            // 対象と数が同じ行が複数あると、抽選結果やボーナスの役がどの行か決まらない
+            if entries
+                .iter()
+                .any(|other| other.symbol == symbol && other.group == group && other.count == pay.count)
+            {
+                return Err(invalid(
+                    format!("paytable[{}]", i),
+                    format!("\"{}\" ×{} の行が重複しています", target, pay.count),
+                ));
+            }
+            entries.push(PayEntry {
+                symbol,
+                group,
+                count: pay.count,
+                rule,
+                multiplier: pay.multiplier,
+            });
+        }
+
+        // --- 内部抽選 ---
+        // symbol と count の組を抽選結果に変換する（両方省略でハズレ）
+        let outcome_of = |field: &str, symbol: &Option<String>, count: Option<usize>| match (symbol, count) {
+            (None, None) => Ok(Outcome::Miss),
+            (Some(glyph), Some(count)) => {
+                let symbol = lookup(format!("{}.symbol", field), glyph)?;
+                if !entries.iter().any(|pay| pay.symbol == symbol && pay.group.is_none() && pay.count == count) {
+                    return Err(invalid(field, format!("\"{}\" ×{} は配当表にありません", glyph, count)));
+                }
+                Ok(Outcome::Win { symbol, count })
+            }
+            _ => Err(invalid(field, "symbol と count は両方指定するか、両方省略（ハズレ）してください")),
+        };
+        // 1行でも weights があれば設定1〜6付きのマシン（weight の行は全設定で同じ重み）
+        let settings = if file
+            .lottery
+            .iter()
+            .chain(file.bonus.iter().flat_map(|bonus| &bonus.lottery))
+            .any(|draw| draw.weights.is_some())
+        {
+            SETTINGS
+        } else {
+            1
+        };
+        let (outcomes, lotteries) = lottery_tables("lottery", &file.lottery, settings, &outcome_of)?;
+
+        // --- フリースピン ---
+        let free_spins = match &file.free_spins {
+            None => None,
+            Some(free_spins) => {
+                let symbol = lookup("free_spins.symbol".to_string(), &free_spins.symbol)?;
+                if !file.symbols[symbol].scatter {
+                    return Err(invalid("free_spins.symbol", "スキャッタ（scatter = true）のシンボルを指定してください"));
+                }
+                if free_spins.count == 0 || free_spins.count > cells {
+                    return Err(invalid(
+                        "free_spins.count",
+                        format!("1〜{}（盤面のマス数）で指定してください", cells),
+                    ));
+                }
+                if free_spins.spins == 0 {
+                    return Err(invalid("free_spins.spins", "1以上の値が必要です"));
+                }
+                Some(FreeSpinsDef {
+                    symbol,
+                    count: free_spins.count,
+                    spins: free_spins.spins,
+                })
+            }
+        };
+        // --- カスケード ---
+        let cascade = match &file.cascade {
+            None => None,
+            Some(cascade) => {
+                if cascade.multipliers.is_empty() {
+                    return Err(invalid("cascade.multipliers", "1つ以上の倍率が必要です"));
+                }
+                if let Some(i) = cascade.multipliers.iter().position(|&multiplier| multiplier == 0) {
+                    return Err(invalid(format!("cascade.multipliers[{}]", i), "1以上の値が必要です"));
+                }
+                Some(CascadeDef {
+                    multipliers: cascade.multipliers.clone(),
+                })
+            }
+        };
+        // --- ホールド・ナッジ ---
+        let percent = |field: &str, chance: u32| {
+            if (1..=100).contains(&chance) {
+                Ok(chance)
+            } else {
+                Err(invalid(field, "1〜100（%）で指定してください"))
+            }
+        };
+        let hold = match &file.hold {
+            None => None,
+            Some(hold) => Some(HoldDef {
+                chance: percent("hold.chance", hold.chance)?,
+            }),
+        };
+        let nudge = match &file.nudge {
+            None => None,
+            Some(nudge) => {
+                if nudge.count == 0 {
+                    return Err(invalid("nudge.count", "1以上の値が必要です"));
+                }
+                Some(NudgeDef {
+                    chance: percent("nudge.chance", nudge.chance)?,
+                    count: nudge.count,
+                })
+            }
+        };
+        // ホールド・ナッジはプレイヤーが盤面を変えるため、停止位置を制御する内部抽選・
+        // 停止時の盤面で決まるカスケード・フリースピンとは組み合わせられない
+        if hold.is_some() || nudge.is_some() {
+            let field = if hold.is_some() { "hold" } else { "nudge" };
+            if !outcomes.is_empty() || cascade.is_some() || free_spins.is_some() {
+                return Err(invalid(
+                    field,
+                    "ホールド・ナッジは内部抽選（lottery）・カスケード・フリースピンのないマシンでのみ使えます",
+                ));
+            }
+        }
+        // --- ダブルアップ ---
+        let gamble = match &file.gamble {
+            None => None,
+            Some(gamble) => {
+                if gamble.ladder == 0 || gamble.ladder > MAX_GAMBLE_LADDER {
+                    return Err(invalid(
+                        "gamble.ladder",
+                        format!("1〜{}で指定してください", MAX_GAMBLE_LADDER),
+                    ));
+                }
+                Some(GambleDef { ladder: gamble.ladder })
+            }
+        };
+        // --- ジャックポット ---
+        let jackpot = match &file.jackpot {
+            None => None,
+            Some(jackpot) => {
+                let symbol = lookup("jackpot.symbol".to_string(), &jackpot.symbol)?;
+                // スキャッタ・グループの行は対象外（ライン・ウェイで揃う1つのシンボルの役のみ）
+                let entry = entries
+                    .iter()
+                    .position(|pay| pay.symbol == symbol && pay.group.is_none() && pay.count == jackpot.count)
+                    .filter(|_| !file.symbols[symbol].scatter)
+                    .ok_or_else(|| {
+                        invalid(
+                            "jackpot",
+                            format!("\"{}\" ×{} は配当表にありません（スキャッタは指定できません）", jackpot.symbol, jackpot.count),
+                        )
+                    })?;
+                if !(1..=100).contains(&jackpot.contribution) {
+                    return Err(invalid("jackpot.contribution", "1〜100（%）で指定してください"));
+                }
+                // 積立ファイル名の一部になるため、パスとして安全な文字に限る
+                if jackpot.pool.is_empty()
+                    || !jackpot
+                        .pool
+                        .chars()
+                        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+                {
+                    return Err(invalid("jackpot.pool", "英数字・'-'・'_' で指定してください"));
+                }
+                Some(JackpotDef {
+                    entry,
+                    contribution: jackpot.contribution,
+                    seed: jackpot.seed,
+                    pool: jackpot.pool.clone(),
+                })
+            }
+        };
+        // 内部抽選は有効ライン上の結果しか制御しないため、スキャッタとは組み合わせられない
+        if !outcomes.is_empty() && file.symbols.iter().any(|symbol| symbol.scatter) {
+            return Err(invalid("symbols", "スキャッタは内部抽選（lottery）のないマシンでのみ使えます"));
+        }
+        // 停止制御はペイライン上の結果を成立させる仕組みのため、ウェイ判定とは組み合わせられない
+        if !outcomes.is_empty() && evaluation == Evaluation::Ways {
+            return Err(invalid("evaluation", "ウェイ判定は内部抽選（lottery）のないマシンでのみ使えます"));
+        }
+        // 連鎖で補充されるシンボルは停止制御の対象外のため、内部抽選とは組み合わせられない
+        if !outcomes.is_empty() && cascade.is_some() {
+            return Err(invalid("cascade", "カスケードは内部抽選（lottery）のないマシンでのみ使えます"));
+        }
+        // 抽選結果・ボーナスの役はシンボルと数で決まるため、グループの行とは組み合わせられない
+        if !outcomes.is_empty() && !groups.is_empty() {
+            return Err(invalid("groups", "シンボルのグループは内部抽選（lottery）のないマシンでのみ使えます"));
+        }
+
+        // --- 停止制御（すべり） ---
+        let slip = match &file.slip {
+            None => SlipControl::new(DEFAULT_MAX_SLIP, Vec::new()),
+            Some(_) if outcomes.is_empty() => {
+                return Err(invalid("slip", "停止制御は内部抽選（lottery）のあるマシンでのみ使えます"));
+            }
+            Some(slip) => {
+                let shortest = reels.iter().map(|strip| strip.len()).min().unwrap_or(0);
+                if slip.max >= shortest {
+                    return Err(invalid(
+                        "slip.max",
+                        format!("最も短いリールのシンボル数（{}）より小さくしてください", shortest),
+                    ));
+                }
+                let mut tables: Vec<(Outcome, Vec<usize>)> = Vec::with_capacity(slip.control.len());
+                for (i, control) in slip.control.iter().enumerate() {
+                    let field = format!("slip.control[{}]", i);
+                    let outcome = outcome_of(&field, &control.symbol, control.count)?;
+                    if !outcomes.contains(&outcome) {
+                        return Err(invalid(field, "抽選テーブル（lottery）にない結果です"));
+                    }
+                    if tables.iter().any(|(table_outcome, _)| *table_outcome == outcome) {
+                        return Err(invalid(field, "同じ結果の制御テーブルが重複しています"));
+                    }
+                    if control.order.is_empty() {
+                        return Err(invalid(format!("{}.order", field), "1つ以上のすべりコマ数が必要です"));
+                    }
+                    for (j, &amount) in control.order.iter().enumerate() {
+                        if amount > slip.max {
+                            return Err(invalid(
+                                format!("{}.order[{}]", field, j),
+                                format!("すべりコマ数は0〜{}で指定してください", slip.max),
+                            ));
+                        }
+                        if control.order[..j].contains(&amount) {
+                            return Err(invalid(format!("{}.order[{}]", field, j), "すべりコマ数が重複しています"));
+                        }
+                    }
+                    tables.push((outcome, control.order.clone()));
+                }
+                SlipControl::new(slip.max, tables)
+            }
+        };
+
+        // --- ボーナス ---
+        if !file.bonus.is_empty() && outcomes.is_empty() {
+            return Err(invalid("bonus", "ボーナスは内部抽選（lottery）のあるマシンでのみ使えます"));
+        }
+        let mut bonuses: Vec<BonusDef> = Vec::with_capacity(file.bonus.len());
+        let mut bonus_outcomes: Vec<Vec<Outcome>> = Vec::with_capacity(file.bonus.len());  // ボーナス中の抽選結果
+        for (i, bonus) in file.bonus.iter().enumerate() {
+            let field = format!("bonus[{}]", i);
+            let kind = match bonus.kind.as_str() {
+                "big" => BonusKind::Big,
+                "reg" => BonusKind::Reg,
+                other => {
+                    return Err(invalid(
+                        format!("{}.kind", field),
+                        format!("\"{}\" は使えません（big または reg）", other),
+                    ));
+                }
+            };
+            let trigger = outcome_of(&field, &Some(bonus.symbol.clone()), Some(bonus.count))?;
+            let Outcome::Win { symbol, count } = trigger else {
+                unreachable!("シンボルを指定した結果は当選役");
+            };
+            if !outcomes.contains(&trigger) {
+                return Err(invalid(field, "抽選テーブル（lottery）にない役ではボーナスが始まりません"));
+            }
+            if bonuses.iter().any(|other| other.symbol == symbol && other.count == count) {
+                return Err(invalid(field, "同じ役のボーナスが重複しています"));
+            }
+            if bonus.games == 0 {
+                return Err(invalid(format!("{}.games", field), "1以上の値が必要です"));
+            }
+            if bonus.payout_cap == Some(0) {
+                return Err(invalid(format!("{}.payout_cap", field), "1以上の値が必要です"));
+            }
+            if bonus.lottery.is_empty() {
+                return Err(invalid(format!("{}.lottery", field), "ボーナス中の抽選テーブルが必要です"));
+            }
+            let (outcomes, lotteries) =
+                lottery_tables(&format!("{}.lottery", field), &bonus.lottery, settings, &outcome_of)?;
+            bonus_outcomes.push(outcomes);
+            bonuses.push(BonusDef::new(kind, symbol, count, bonus.games, bonus.payout_cap, lotteries));
+        }
+        // ボーナス中にボーナスが始まることはない
+        for (i, outcomes) in bonus_outcomes.iter().enumerate() {
+            let nested = outcomes.iter().position(|outcome| {
+                bonuses.iter().any(|bonus| {
+                    *outcome == Outcome::Win {
+                        symbol: bonus.symbol,
+                        count: bonus.count,
+                    }
+                })
+            });
+            if let Some(j) = nested {
+                return Err(invalid(
+                    format!("bonus[{}].lottery[{}]", i, j),
+                    "ボーナス中の抽選テーブルにボーナスの役は入れられません",
+                ));
+            }
+        }
+
+        // --- タイミング ---
+        if file.timing.spin_step_ms == 0 {
+            return Err(invalid("timing.spin_step_ms", "0より大きい値が必要です"));
+        }
+
+        let machine = Self {
+            name: file.name,
+            symbols: file
+                .symbols
+                .into_iter()
+                .map(|symbol| SymbolDef {
+                    glyph: symbol.glyph,
+                    name: symbol.name,
+                    wild: symbol.wild,
+                    wild_multiplier: symbol.wild_multiplier.unwrap_or(1),
+                    scatter: symbol.scatter,
+                })
+                .collect(),
+            reels,
+            rows: file.rows,
+            paylines: PaylineSet::new(file.paylines),
+            evaluation,
+            line_options,
+            paytable: Paytable::new(entries, groups),
+            spin_step: Duration::from_millis(file.timing.spin_step_ms),
+            lotteries,
+            slip,
+            bonuses,
+            free_spins,
+            cascade,
+            hold,
+            nudge,
+            gamble,
+            jackpot,
+        };
+        machine.check_lottery()?;
+        Ok(machine)
+    }
+
+    /// 抽選テーブルのすべての結果が、どの有効ライン数でもリール配列上で成立させられるか確認する
+    /// 成立しない結果が抽選されるとリールを止められないため、読み込み時に弾く
+    /// ボーナス中の抽選テーブルも同じく確認する
+    fn check_lottery(&self) -> Result<(), MachineError> {
+        let tables = self
+            .lotteries
+            .first()
+            .map(|lottery| ("lottery".to_string(), lottery))
+            .into_iter()
+            .chain(self.bonuses.iter().enumerate().filter_map(|(i, bonus)| {
+                bonus.lotteries().first().map(|lottery| (format!("bonus[{}].lottery", i), lottery))
+            }));
+        let unstopped = vec![None; self.reels.len()];
+        for (field, lottery) in tables {
+            for (i, entry) in lottery.entries().iter().enumerate() {
+                for &lines in &self.line_options {
+                    let paylines = self.paylines.active(lines);
+                    if !lottery::reachable(self, paylines, entry.outcome, &unstopped) {
+                        return Err(invalid(
+                            format!("{}[{}]", field, i),
+                            format!("{}ライン有効のとき、この結果になる停止位置がありません", lines),
+                        ));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 抽選テーブルの行を検証し、設定ごとの抽選テーブルを作る
+///
+/// # 引数
+/// * `field` - エラーメッセージに使う項目名（"lottery" など）
+/// * `rows` - 抽選テーブルの行
+/// * `settings` - 設定の数（設定のないマシンは1）
+/// * `outcome_of` - symbol と count の組を抽選結果に変換する関数
+///
+/// # 戻り値
+/// 行ごとの抽選結果と、設定ごとの抽選テーブル（行がなければどちらも空）
+fn lottery_tables(
+    field: &str,
+    rows: &[LotteryFile],
+    settings: usize,
+    outcome_of: &impl Fn(&str, &Option<String>, Option<usize>) -> Result<Outcome, MachineError>,
+) -> Result<(Vec<Outcome>, Vec<Lottery>), MachineError> {
+    let mut outcomes: Vec<Outcome> = Vec::with_capacity(rows.len());
+    let mut weights: Vec<Vec<u32>> = Vec::with_capacity(rows.len());  // 行ごとの設定別の重み
+    for (i, draw) in rows.iter().enumerate() {
+        let field = format!("{}[{}]", field, i);
+        let outcome = outcome_of(&field, &draw.symbol, draw.count)?;
+        if outcomes.contains(&outcome) {
+            return Err(invalid(field, "同じ結果が重複しています"));
+        }
+        let row = match (draw.weight, &draw.weights) {
+            (Some(0), None) => {
+                return Err(invalid(format!("{}.weight", field), "1以上の値が必要です"));
+            }
+            (Some(weight), None) => vec![weight; settings],
+            (None, Some(row)) if row.len() == SETTINGS => row.clone(),
+            (None, Some(_)) => {
+                return Err(invalid(
+                    format!("{}.weights", field),
+                    format!("設定1〜{}の{}個の重みが必要です", SETTINGS, SETTINGS),
+                ));
+            }
+            _ => return Err(invalid(field, "weight と weights のどちらか一方を指定してください")),
+        };
+        if row.iter().all(|&weight| weight == 0) {
+            return Err(invalid(format!("{}.weights", field), "どの設定でも重みが0です"));
+        }
+        outcomes.push(outcome);
+        weights.push(row);
+    }
+    let mut lotteries = Vec::new();
+    if !outcomes.is_empty() {
+        for setting in 0..settings {
+            let table: Vec<LotteryEntry> = outcomes
+                .iter()
+                .zip(&weights)
+                .map(|(&outcome, row)| LotteryEntry {
+                    outcome,
+                    weight: row[setting],
+                })
+                .collect();
+            if table.iter().all(|entry| entry.weight == 0) {
+                return Err(invalid(field, format!("設定{}の重みがすべて0です", setting + 1)));
+            }
+            lotteries.push(Lottery::new(table));
+        }
+    }
+    Ok((outcomes, lotteries))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 検証を通る最小限のマシン定義（各テストで一部を書き換えて壊す）
+    const VALID_TOML: &str = r#"
+        name = "テスト"
+        rows = 1
+        line_options = [1]
+        reels = [["🍒", "🔔"], ["🍒", "🔔"], ["🍒", "🔔"]]
+        paylines = [[0, 0, 0]]
+
+        [timing]
+        spin_step_ms = 35
+
+        [[symbols]]
+        glyph = "🍒"
+        name = "チェリー"
+
+        [[symbols]]
+        glyph = "🔔"
+        name = "ベル"
+
+        [[paytable]]
+        symbol = "🍒"
+        count = 3
+        multiplier = 5
+    "#;
+
+    /// VALID_TOML と同じ内容のJSON
+    const VALID_JSON: &str = r#"{
+        "name": "テスト",
+        "rows": 1,
+        "line_options": [1],
+        "reels": [["🍒", "🔔"], ["🍒", "🔔"], ["🍒", "🔔"]],
+        "paylines": [[0, 0, 0]],
+        "timing": { "spin_step_ms": 35 },
+        "symbols": [{ "glyph": "🍒", "name": "チェリー" }, { "glyph": "🔔", "name": "ベル" }],
+        "paytable": [{ "symbol": "🍒", "count": 3, "multiplier": 5 }]
+    }"#;
+
+    /// 定義の一部を書き換える（書き換え元が見つからなければテストの誤り）
+    fn replaced(source: &str, from: &str, to: &str) -> String {
+        assert!(source.contains(from), "書き換え元がありません: {}", from);
+        source.replacen(from, to, 1)
+    }
+
+    #[test]
+    fn valid_definitions_load_from_toml_and_json() {
+        let toml = MachineDef::from_toml(VALID_TOML, Path::new("test.toml")).unwrap();
+        let json = MachineDef::from_json(VALID_JSON, Path::new("test.json")).unwrap();
+        for machine in [toml, json] {
+            assert_eq!(machine.reels, vec![vec![0, 1]; 3]);
+            assert_eq!(machine.max_lines(), 1);
+        }
+    }
+
+    #[test]
+    fn inconsistent_toml_reports_the_invalid_field() {
+        // (書き換え元, 書き換え後, 期待するエラーの項目)
+        let cases = [
+            (r#"reels = [["🍒", "🔔"], "#, r#"reels = [["🍒", "👻"], "#, "reels[0][1]"),                 // 未定義のシンボル
+            (r#"reels = [["🍒", "🔔"], "#, "reels = [[], ", "reels[0]"),                                 // 空のリール
+            (r#"reels = [["🍒", "🔔"], ["🍒", "🔔"], ["🍒", "🔔"]]"#, "reels = []", "reels"),            // リールなし
+            ("paylines = [[0, 0, 0]]", "paylines = [[0, 1, 0]]", "paylines[0]"),                         // 表示範囲外の段
+            ("paylines = [[0, 0, 0]]", "paylines = [[0, 0]]", "paylines[0]"),                            // リール数と違う長さ
+            ("paylines = [[0, 0, 0]]", "paylines = []", "paylines"),                                     // ペイラインなし
+            ("line_options = [1]", "line_options = [2]", "line_options"),                                // ペイラインより多い
+            ("rows = 1", "rows = 0", "rows"),
+            ("spin_step_ms = 35", "spin_step_ms = 0", "timing.spin_step_ms"),
+            (r#"glyph = "🔔""#, r#"glyph = "🍒""#, "symbols[1].glyph"),                                  // 重複したシンボル
+            (r#"name = "ベル""#, "name = \"ベル\"\nwild_multiplier = 2", "symbols[1].wild_multiplier"),  // ワイルドでない
+            (r#"symbol = "🍒""#, r#"symbol = "👻""#, "paytable[0].symbol"),                              // 配当表の未定義のシンボル
+            ("count = 3", "count = 4", "paytable[0].count"),                                             // リール数より多い
+            ("count = 3", "count = 3\nrule = \"middle\"", "paytable[0].rule"),
+        ];
+        for (from, to, expected) in cases {
+            let source = replaced(VALID_TOML, from, to);
+            match MachineDef::from_toml(&source, Path::new("test.toml")) {
+                Err(MachineError::Invalid { field, .. }) => assert_eq!(field, expected, "{} → {}", from, to),
+                Err(other) => panic!("{} → {}: 検証エラーになるはずが {}", from, to, other),
+                Ok(_) => panic!("{} → {}: 読み込めてしまった", from, to),
+            }
+        }
+    }
+
+    #[test]
+    fn malformed_toml_is_a_parse_error() {
+        let cases = [
+            replaced(VALID_TOML, "rows = 1", "rows = "),                     // 書式の誤り
+            replaced(VALID_TOML, "rows = 1", "rows = \"1\""),                // 型の誤り
+            replaced(VALID_TOML, r#"name = "テスト""#, ""),                  // 必須の項目がない
+            replaced(VALID_TOML, "rows = 1", "rows = 1\ncolour = \"red\""),  // 知らない項目
+        ];
+        for source in cases {
+            match MachineDef::from_toml(&source, Path::new("broken.toml")) {
+                Err(MachineError::Parse { path, .. }) => assert_eq!(path, Path::new("broken.toml")),
+                Err(other) => panic!("書式エラーになるはずが {}", other),
+                Ok(_) => panic!("読み込めてしまった:\n{}", source),
+            }
+        }
+    }
+
+    #[test]
+    fn malformed_json_reports_parse_and_validation_errors() {
+        let broken = replaced(VALID_JSON, r#""rows": 1,"#, r#""rows": 1"#);
+        assert!(matches!(
+            MachineDef::from_json(&broken, Path::new("broken.json")),
+            Err(MachineError::Parse { .. })
+        ));
+        let out_of_range = replaced(VALID_JSON, r#""paylines": [[0, 0, 0]]"#, r#""paylines": [[0, 0, 3]]"#);
+        assert!(matches!(
+            MachineDef::from_json(&out_of_range, Path::new("test.json")),
+            Err(MachineError::Invalid { field, .. }) if field == "paylines[0]"
+        ));
+    }
+
+    #[test]
+    fn missing_file_is_an_io_error() {
+        let path = Path::new("machines/does-not-exist.toml");
+        assert!(matches!(MachineDef::load(path), Err(MachineError::Io { path: failed, .. }) if failed == path));
+    }
+}
@@ -1,246 +1,229 @@
-// リールモジュールをインポート（同じディレクトリのreel.rsファイル）
-mod reel;
+// コマンドライン引数の解析モジュールをインポート（cli.rs）
+mod cli;
+// ターミナル描画のバックエンドモジュールをインポート（ui.rs）
+mod ui;
 
 // クロスターミナルライブラリから必要な機能をインポート
-// これらはターミナル操作（画面クリア、カーソル移動、色設定など）に使用
+// これらはターミナル操作（画面クリア、カーソル移動、キー入力など）に使用
 use crossterm::{
     cursor,                                                    // カーソル移動機能
     event::{self, Event, KeyCode, KeyEvent},                  // キーボード入力イベント処理
     execute,                                                   // ターミナルコマンド実行マクロ
-    style::{Color, Print, SetForegroundColor},                // 色設定と文字出力
     terminal::{self, ClearType},                              // ターミナル制御（画面クリアなど）
 };
-// リールモジュールから必要な関数と構造体をインポート
-use reel::{check_winnings, Reel, DISPLAY_SIZE, PAYLINES};
+// スロットマシンのエンジン（ゲーム進行・マシン定義・乱数・操作・画面描画）
+use gh_slot::{Applied, MachineDef, ReelCommand, Renderer, SessionRng, SlotMachine, View};
+// リールの判定方式（ウェイ判定ではライン数を指定できない）
+use gh_slot::reel::Evaluation;
+// シミュレーションとパーシート
+use gh_slot::{parsheet, simulate};
+use gh_slot::simulate::SimulationConfig;
+// セッションログの記録と再生
+use gh_slot::session::{Entry, Recording, SessionLog, SpinResult};
+// ダブルアップの予想（キー入力の割り当て）
+use gh_slot::gamble::Guess;
+// 全リールの表示を進めるゲームループの時計
+use gh_slot::ticker::{Ticker, FRAME};
+// ライブプレイで使う実時間の時計
+use gh_slot::clock::SystemClock;
+// コマンドライン引数の解析結果
+use cli::Command;
+// ターミナルへの描画
+use ui::Terminal;
 // 標準ライブラリから入出力と時間機能をインポート
-use std::io::{self, stdout};                                  // 入出力エラー処理と標準出力
+use std::collections::VecDeque;                               // リプレイで照合待ちの精算結果
+use std::io::{self, stdout, IsTerminal, Write};              // 入出力エラー処理と標準出力
+use std::path::Path;                                          // マシン定義・ログファイルのパス
 use std::time::Duration;                                      // 時間間隔指定
-// 非同期処理のためのtokioライブラリから時間待機機能をインポート
+// 入力スレッドからゲームループへ操作を送るチャンネル
 use std::sync::mpsc::{self, Sender, Receiver};
 use std::thread;
-/// スロットマシン全体を管理する構造体
-/// 3つのリールと前回の回転状態を保持
-struct SlotMachine {
-    reels: [Reel; 3],                // 3つのリールを配列で管理
-    last_spinning_state: [bool; 3],  // 前回の各リールの回転状態（状態変化検出用）
-}
 
-impl SlotMachine {
-    /// 新しいスロットマシンインスタンスを作成
-    /// 各リールには0, 1, 2のIDを割り当て
-    fn new() -> Self {
-        Self {
-            reels: [Reel::new(0), Reel::new(1), Reel::new(2)],
-            last_spinning_state: [false, false, false],
+/// メイン関数
+/// スロットマシンゲームのエントリーポイント
+/// コマンドライン引数に応じてゲームまたはシミュレーションを実行する
+fn main() -> io::Result<()> {
+    // 引数とマシン定義のエラーはターミナルを初期化する前に表示する
+    let command = cli::parse(std::env::args().skip(1)).unwrap_or_else(|message| exit_with(&message));
+    match command {
+        Command::Help => {
+            println!("{}", cli::USAGE);
+            Ok(())
         }
-    }
-
-    /// 全てのリールの回転を開始する非同期関数
-    /// 各リールを並行して回転させるために非同期タスクを作成
-    fn start_all_reels(&self) {
-        // 全リールの回転開始フラグを設定
-        for reel in &self.reels {
-            reel.start_spinning();
+        Command::Play { machine: path, seed, log, setting } => {
+            let machine = load_machine(path.as_deref()).unwrap_or_else(|message| exit_with(&message));
+            check_setting(&machine, setting).unwrap_or_else(|message| exit_with(&message));
+            let mut slot_machine = SlotMachine::new(machine, session_rng(seed), setting);
+            slot_machine.connect_jackpot();
+            // 既定のログはセッションごとに別のファイルにする（同時に遊ぶセッションや他のユーザーのログを上書きしない）
+            let log_path = log.unwrap_or_else(|| {
+                std::env::temp_dir().join(format!("gh-slot-session-{}-{}.log", std::process::id(), slot_machine.seed()))
+            });
+            let log = SessionLog::create(
+                &log_path,
+                slot_machine.seed(),
+                path.as_deref(),
+                // 隠した設定はログに書かない（シードから同じ設定が抽選されるので、指定があったときだけ残す）
+                setting,
+            )
+            .unwrap_or_else(|err| {
+                exit_with(&format!("セッションログを作成できません: {}: {}", log_path.display(), err))
+            });
+            run_game(slot_machine, log, &log_path)
         }
-        // 各リールのスピンループを並行実行
-        for reel in &self.reels {
-            let reel_clone = reel.clone();
-            std::thread::spawn(move || {
-                let rt = tokio::runtime::Runtime::new().unwrap();
-                rt.block_on(reel_clone.spin_loop());
+        Command::Simulate { machine, spins, lines, seed, setting } => {
+            let machine = load_machine(machine.as_deref()).unwrap_or_else(|message| exit_with(&message));
+            let lines = active_lines(&machine, lines).unwrap_or_else(|message| exit_with(&message));
+            check_setting(&machine, setting).unwrap_or_else(|message| exit_with(&message));
+            let config = SimulationConfig {
+                spins,
+                lines,
+                setting: setting.unwrap_or(1),
+            };
+            let mut rng = session_rng(seed);
+            let report = simulate::run(&machine, &config, &mut rng);
+            report.write_to(&machine, &mut stdout())?;
+            println!();
+            println!("シード: {}（--seed {} で同じ結果を再現できます）", rng.seed(), rng.seed());
+            Ok(())
+        }
+        Command::Replay { log } => {
+            let recording = Recording::load(&log).unwrap_or_else(|message| exit_with(&message));
+            let machine = load_machine(recording.machine.as_deref()).unwrap_or_else(|message| exit_with(&message));
+            if !replay(&recording, machine) {
+                std::process::exit(1);
+            }
+            Ok(())
+        }
+        Command::ParSheet { machine, lines, format, output } => {
+            let machine = load_machine(machine.as_deref()).unwrap_or_else(|message| exit_with(&message));
+            let lines = active_lines(&machine, lines).unwrap_or_else(|message| exit_with(&message));
+            // 組み合わせが多いと時間がかかるので、端末なら進み具合を標準エラーに出す（結果の出力には混ぜない）
+            let show_progress = io::stderr().is_terminal();
+            let sheet = parsheet::compute_with_progress(&machine, lines, |done, total| {
+                if show_progress {
+                    eprint!("\r計算中: {:>3}%（{} / {} 通り）", done * 100 / total, done, total);
+                }
             });
+            if show_progress {
+                execute!(io::stderr(), cursor::MoveToColumn(0), terminal::Clear(ClearType::CurrentLine))?;
+            }
+            match output {
+                Some(path) => {
+                    let mut file = io::BufWriter::new(std::fs::File::create(&path)?);
+                    sheet.write_to(&machine, format, &mut file)?;
+                    file.flush()
+                }
+                None => sheet.write_to(&machine, format, &mut stdout()),
+            }
         }
     }
+}
 
-    /// 指定されたインデックスのリールを停止する
-    /// index: 停止するリールの番号（0:左, 1:中央, 2:右）
-    fn stop_reel(&self, index: usize) {
-        if index < 3 {
-            self.reels[index].request_stop();
-        }
+/// 設定の指定を確認する（設定のないマシンでは指定できない）
+fn check_setting(machine: &MachineDef, setting: Option<usize>) -> Result<(), String> {
+    if setting.is_some() && !machine.has_settings() {
+        return Err(format!("マシン「{}」には設定がありません（--setting は使えません）", machine.name));
     }
+    Ok(())
+}
 
-    /// リールの回転状態が前回と変化したかを確認
-    /// 表示の更新が必要かどうかを判断するために使用
-    fn has_state_changed(&mut self) -> bool {
-        // 現在の回転状態を取得
-        let current_state = [
-            self.reels[0].is_spinning(),
-            self.reels[1].is_spinning(),
-            self.reels[2].is_spinning(),
-        ];
-        
-        // 前回の状態と比較
-        let changed = current_state != self.last_spinning_state;
-        // 今回の状態を保存
-        self.last_spinning_state = current_state;
-        changed
+/// 有効ライン数の指定を確認する（未指定ならマシンの最大ライン数）
+fn active_lines(machine: &MachineDef, lines: Option<usize>) -> Result<usize, String> {
+    if lines.is_some() && machine.evaluation == Evaluation::Ways {
+        return Err(format!("マシン「{}」はウェイ判定のため --lines は使えません", machine.name));
     }
+    let max_lines = machine.max_lines();
+    match lines {
+        Some(lines) if lines > max_lines => Err(format!("--lines は1〜{}で指定してください", max_lines)),
+        Some(lines) => Ok(lines),
+        None => Ok(max_lines),
+    }
+}
 
-    /// リールの表示を行う関数
-    /// スロットマシンの見た目をターミナルに描画
-    fn display_reels(&self) -> io::Result<()> {
-        // カーソルを指定位置に移動してから表示
-        execute!(stdout(), cursor::MoveTo(0, 2))?;
-
-        // 各リールから現在表示すべきシンボルを取得
-        let reel_symbols: Vec<[&str; DISPLAY_SIZE]> = self
-            .reels
-            .iter()
-            .map(|reel| reel.get_visible_symbols())
-            .collect();
-
-        // 各行を個別に出力して正確な表示を確保
-        execute!(stdout(), cursor::MoveTo(0, 2))?;
-        execute!(stdout(), Print("┌────┬────┬────┐"))?;
-        
-        execute!(stdout(), cursor::MoveTo(0, 3))?;
-        execute!(
-            stdout(),
-            Print(format!(
-                "│ {} │ {} │ {} │",
-                reel_symbols[0][0], reel_symbols[1][0], reel_symbols[2][0]
-            ))
-        )?;
-        
-        execute!(stdout(), cursor::MoveTo(0, 4))?;
-        execute!(stdout(), Print("├────┼────┼────┤"))?;
-        
-        execute!(stdout(), cursor::MoveTo(0, 5))?;
-        execute!(
-            stdout(),
-            Print(format!(
-                "│ {} │ {} │ {} │",
-                reel_symbols[0][1], reel_symbols[1][1], reel_symbols[2][1]
-            ))
-        )?;
-        
-        execute!(stdout(), cursor::MoveTo(0, 6))?;
-        execute!(stdout(), Print("├────┼────┼────┤"))?;
-        
-        execute!(stdout(), cursor::MoveTo(0, 7))?;
-        execute!(
-            stdout(),
-            Print(format!(
-                "│ {} │ {} │ {} │",
-                reel_symbols[0][2], reel_symbols[1][2], reel_symbols[2][2]
-            ))
-        )?;
-        
-        execute!(stdout(), cursor::MoveTo(0, 8))?;
-        execute!(stdout(), Print("└────┴────┴────┘"))?;
+/// セッションの乱数生成器を用意する（シード未指定ならOSの乱数でシードを決める）
+fn session_rng(seed: Option<u64>) -> SessionRng {
+    match seed {
+        Some(seed) => SessionRng::seeded(seed),
+        None => SessionRng::from_entropy(),
+    }
+}
 
-        // リールの状態表示（各リールが回転中か停止中かを表示）
-        let status: Vec<String> = self
-            .reels
-            .iter()
-            .enumerate()
-            .map(|(i, reel)| {
-                if reel.is_spinning() {
-                    format!("リール{}: 回転中", i + 1)
-                } else {
-                    format!("リール{}: 停止", i + 1)
+/// セッションログを画面なしで再生し、記録された精算結果と照合する
+/// 操作は記録された時刻のまま適用するため、待ち時間なしで同じゲームを再現できる
+///
+/// # 戻り値
+/// すべての精算結果が記録と一致すればtrue
+fn replay(recording: &Recording, machine: MachineDef) -> bool {
+    println!("🎰 リプレイ: {}（シード {}）", machine.name, recording.seed);
+    let mut slot_machine = SlotMachine::new(machine, SessionRng::seeded(recording.seed), recording.setting);
+    let mut produced: VecDeque<SpinResult> = VecDeque::new();  // 照合待ちの再生結果
+    let mut spins = 0;
+    let mut matched = true;
+    for entry in &recording.entries {
+        match entry {
+            Entry::Command { at_ms, command } => {
+                if *command == ReelCommand::Exit {
+                    break;
                 }
-            })
-            .collect();
-
-        execute!(stdout(), cursor::MoveTo(0, 10))?;
-        execute!(stdout(), Print(&status[0]))?;
-        execute!(stdout(), cursor::MoveTo(0, 11))?;
-        execute!(stdout(), Print(&status[1]))?;
-        execute!(stdout(), cursor::MoveTo(0, 12))?;
-        execute!(stdout(), Print(&status[2]))?;
-
-        // 結果表示エリアをクリア
-        execute!(stdout(), cursor::MoveTo(0, 14))?;
-        execute!(stdout(), terminal::Clear(ClearType::FromCursorDown))?;
-
-        // 当選チェック（全リール停止時のみ）
-        if !self.reels.iter().any(|reel| reel.is_spinning()) {
-            let winning_lines = check_winnings(&self.reels);
-            if !winning_lines.is_empty() {
-                // 当選時の表示
-                execute!(stdout(), cursor::MoveTo(0, 14))?;
-                execute!(stdout(), SetForegroundColor(Color::Yellow))?;
-                execute!(stdout(), Print("🎉 当選! 🎉"))?;
-                execute!(stdout(), cursor::MoveTo(0, 15))?;
-                execute!(stdout(), Print("当選ライン: "))?;
-                for line in &winning_lines {
-                    execute!(stdout(), Print(format!("{} ", line + 1)))?;
+                if let Applied::Settled(result) = slot_machine.apply(*command, *at_ms) {
+                    produced.push_back(result);
+                }
+            }
+            Entry::Result(expected) => {
+                spins += 1;
+                match produced.pop_front() {
+                    Some(actual) if actual == *expected => {
+                        println!("回転{}: 停止位置・配当 {} ✓", spins, actual);
+                    }
+                    Some(actual) => {
+                        println!("回転{}: 不一致（記録: {} / 再生: {}）", spins, expected, actual);
+                        matched = false;
+                    }
+                    None => {
+                        println!("回転{}: 再生では精算されませんでした（記録: {}）", spins, expected);
+                        matched = false;
+                    }
                 }
-                execute!(stdout(), SetForegroundColor(Color::White))?;
-                
-                // 当選ラインの詳細表示
-                execute!(stdout(), cursor::MoveTo(0, 17))?;
-                self.display_paylines(&winning_lines)?;
-            } else {
-                // ハズレ時の表示
-                execute!(stdout(), cursor::MoveTo(0, 14))?;
-                execute!(stdout(), Print("残念、ハズレです"))?;
+            }
+            // 積立額は他のセッションと共有のため再現せず、記録された額をそのまま加算する
+            Entry::Jackpot(amount) => {
+                slot_machine.deposit_jackpot(*amount);
+                println!("回転{}: ジャックポット {}クレジット（記録から加算）", spins, amount);
             }
         }
-
-        Ok(())
     }
-
-    /// 初期画面を表示する関数
-    /// ゲーム開始時にタイトル、リール、操作説明を表示
-    fn display_initial_screen(&self) -> io::Result<()> {
-        // 画面をクリアして初期表示
-        execute!(stdout(), terminal::Clear(ClearType::All))?;
-        execute!(stdout(), cursor::MoveTo(0, 0))?;
-
-        // タイトル
-        execute!(stdout(), Print("🎰 スロットマシン 🎰"))?;
-
-        // リール表示
-        self.display_reels()?;
-
-        // 操作説明を下部に表示（固定位置）
-        execute!(stdout(), cursor::MoveTo(0, 20))?;
-        execute!(stdout(), Print("操作方法:"))?;
-        execute!(stdout(), cursor::MoveTo(0, 21))?;
-        execute!(stdout(), Print("スペースキー: 全リール回転開始"))?;
-        execute!(stdout(), cursor::MoveTo(0, 22))?;
-        execute!(stdout(), Print("←キー: 左リール停止"))?;
-        execute!(stdout(), cursor::MoveTo(0, 23))?;
-        execute!(stdout(), Print("↓キー: 中リール停止"))?;
-        execute!(stdout(), cursor::MoveTo(0, 24))?;
-        execute!(stdout(), Print("→キー: 右リール停止"))?;
-        execute!(stdout(), cursor::MoveTo(0, 25))?;
-        execute!(stdout(), Print("ESCキー: ゲーム終了"))?;
-
-        Ok(())
+    for actual in produced {
+        println!("記録にない精算が発生しました: {}", actual);
+        matched = false;
     }
-
-    /// 有効ラインの表示
-    /// 当選ライン情報を画面に表示する
-    fn display_paylines(&self, winning_lines: &[usize]) -> io::Result<()> {
-        execute!(stdout(), Print("有効ライン:"))?;
-        // 全てのペイラインを表示し、当選したラインをマークする
-        for (i, line) in PAYLINES.iter().enumerate() {
-            let status = if winning_lines.contains(&i) { "🎯" } else { "  " };
-            execute!(stdout(), cursor::MoveTo(0, (18 + i) as u16))?;
-            execute!(
-                stdout(),
-                Print(format!(
-                    "{} ライン{}: [{}, {}, {}]",
-                    status,
-                    i + 1,
-                    line[0] + 1,
-                    line[1] + 1,
-                    line[2] + 1
-                ))
-            )?;
+    println!("最終クレジット: {}", slot_machine.wallet().balance());
+    if let Some(stats) = slot_machine.stats() {
+        if recording.reveals_setting() {
+            stats
+                .write_reveal(slot_machine.machine(), slot_machine.setting(), &mut stdout())
+                .unwrap();
+        } else {
+            println!("設定はセッションの終了後に公開されます（ログに終了の記録がありません）");
         }
-        Ok(())
     }
+    println!("{}", if matched { "すべての結果が一致しました" } else { "記録と一致しない結果があります" });
+    matched
 }
 
-/// メイン関数
-/// スロットマシンゲームのエントリーポイント
-/// 非同期実行とターミナル制御を行う
-fn main() -> io::Result<()> {
+/// エラーメッセージと使い方を表示して終了する
+fn exit_with(message: &str) -> ! {
+    eprintln!("{}", message);
+    eprintln!();
+    eprintln!("{}", cli::USAGE);
+    std::process::exit(2);
+}
+
+/// ターミナルでスロットマシンゲームを実行する
+/// メインスレッドはキー入力をチャンネルで送るだけで、ゲームループのスレッドが1つのティッカー
+/// （FRAME 間隔）で操作の適用・全リールの表示の更新・描画を順に行う
+/// 操作はすべて時刻付きでセッションログに記録する（replay で再現できる）
+fn run_game(mut slot_machine: SlotMachine, mut log: SessionLog, log_path: &Path) -> io::Result<()> {
     // ターミナルの初期化
     terminal::enable_raw_mode()?;
     execute!(stdout(), terminal::Clear(ClearType::All))?;
@@ -249,32 +232,88 @@ fn main() -> io::Result<()> {
     let (tx, rx): (Sender<ReelCommand>, Receiver<ReelCommand>) = mpsc::channel();
 
     // スロットマシンのインスタンスをスレッド用に用意
-    let mut slot_machine = SlotMachine::new();
+    // 矢印キーの割り当てに使うリール数（左端・中央・右端）
+    let reel_count = slot_machine.reels().len();
+    // セッション開始時刻（操作の時刻とリール位置はここからの経過ミリ秒で表す）
+    let clock = SystemClock::start();
 
-    // リール制御・描画スレッド起動
+    // ゲームループ（リール制御・描画）スレッド起動
+    // 全リールの表示はこのループの1つの時計で進める（リールごとのスレッドは持たない）
     let handle = thread::spawn(move || {
+        let mut ticker = Ticker::new(clock, FRAME);
+        let mut screen = Terminal;
         // 初期画面表示
-        slot_machine.display_initial_screen().unwrap();
+        screen.render(View::Initial, &slot_machine.snapshot()).unwrap();
         loop {
             // コマンド受信（ノンブロッキング）
             while let Ok(cmd) = rx.try_recv() {
+                // 操作の時刻はここで確定させる（リプレイでは同じ時刻で適用し直す）
+                let now_ms = ticker.now_ms();
+                let cmd = slot_machine.resolve(cmd);
+                log.command(now_ms, cmd).unwrap();
+                if cmd == ReelCommand::Exit {
+                    return slot_machine;
+                }
+                let was_showing_help = slot_machine.show_help();
+                let applied = slot_machine.apply(cmd, now_ms);
+                // 操作の結果（ナッジで動いたリールなど）を描画に反映する
+                slot_machine.tick(now_ms);
+                match applied {
+                    Applied::Started | Applied::Nothing => {}
+                    Applied::Settled(result) => {
+                        log.result(&result).unwrap();
+                        if let Some(amount) = slot_machine.take_unlogged_jackpot() {
+                            log.jackpot(amount).unwrap();
+                        }
+                        // ナッジ・ダブルアップはリールの状態が変わらないので、ここで描画する
+                        if !slot_machine.show_help() {
+                            screen.render(View::Reels, &slot_machine.snapshot()).unwrap();
+                        }
+                    }
+                }
                 match cmd {
-                    ReelCommand::StartAll => {
-                        slot_machine.start_all_reels();
+                    ReelCommand::StartAll | ReelCommand::ChangeLines | ReelCommand::Hold(_) | ReelCommand::Collect
+                        if !slot_machine.show_help() =>
+                    {
+                        // ベット直後のクレジットやお知らせをすぐに反映
+                        screen.render(View::Reels, &slot_machine.snapshot()).unwrap();
+                    }
+                    ReelCommand::ChangeBet => {
+                        if slot_machine.show_help() {
+                            // ヘルプ画面の払い出し額も掛け枚数に合わせて更新
+                            screen.render(View::Help, &slot_machine.snapshot()).unwrap();
+                        } else {
+                            screen.render(View::Reels, &slot_machine.snapshot()).unwrap();
+                        }
                     }
-                    ReelCommand::Stop(idx) => {
-                        slot_machine.stop_reel(idx);
+                    ReelCommand::ToggleHelp if slot_machine.show_help() != was_showing_help => {
+                        if slot_machine.show_help() {
+                            screen.render(View::Help, &slot_machine.snapshot()).unwrap();
+                        } else {
+                            screen.render(View::Initial, &slot_machine.snapshot()).unwrap();
+                        }
                     }
-                    ReelCommand::Exit => return,
+                    // 停止操作はリールの状態変化として下で描画する
+                    _ => {}
                 }
             }
-            // 状態変化チェック
-            let state_changed = slot_machine.has_state_changed();
+            // 回転中・すべり中のリールの表示をこのティックの時刻まで進める
+            let now_ms = ticker.now_ms();
+            slot_machine.tick(now_ms);
+            // 状態変化チェック（停止した時点で精算済み）
+            // カスケードの演出はコマが進んだときも描画する
+            // ジャックポットの積立額は他のセッションでも変わるので、定期的に読み直す
+            let state_changed = slot_machine.has_state_changed()
+                | slot_machine.advance_cascade(now_ms)
+                | slot_machine.poll_jackpot(now_ms);
             // 回転中または変化時のみ描画
-            if slot_machine.reels.iter().any(|r| r.is_spinning()) || state_changed {
-                slot_machine.display_reels().unwrap();
+            // ヘルプ画面の表示中はリールを描画しない
+            let needs_redraw = slot_machine.frame().is_moving() || state_changed;
+            if needs_redraw && !slot_machine.show_help() {
+                screen.render(View::Reels, &slot_machine.snapshot()).unwrap();
             }
-            std::thread::sleep(Duration::from_millis(35));
+            // 次のティックまで待つ（処理に時間がかかっても間隔はずれない）
+            ticker.wait();
         }
     });
 
@@ -286,9 +325,40 @@ fn main() -> io::Result<()> {
                     KeyCode::Char(c) if c == ' ' || c == '\u{3000}' || c.is_whitespace() => {
                         tx.send(ReelCommand::StartAll).unwrap();
                     }
+                    KeyCode::Char('b') | KeyCode::Char('B') => {
+                        tx.send(ReelCommand::ChangeBet).unwrap();
+                    }
+                    KeyCode::Char('l') | KeyCode::Char('L') => {
+                        tx.send(ReelCommand::ChangeLines).unwrap();
+                    }
+                    KeyCode::Char('h') | KeyCode::Char('H') => {
+                        tx.send(ReelCommand::ToggleHelp).unwrap();
+                    }
+                    KeyCode::Char(c @ '1'..='9') => {
+                        // 数字キー: N番目のリールを停止（存在しないリールは無視される）
+                        let index = c as usize - '1' as usize;
+                        tx.send(ReelCommand::Stop(index)).unwrap();
+                    }
+                    // ダブルアップの予想と受け取り
+                    KeyCode::Char('z') | KeyCode::Char('Z') => { tx.send(ReelCommand::Gamble(Guess::Red)).unwrap(); }
+                    KeyCode::Char('x') | KeyCode::Char('X') => { tx.send(ReelCommand::Gamble(Guess::Black)).unwrap(); }
+                    KeyCode::Char('a') | KeyCode::Char('A') => { tx.send(ReelCommand::Gamble(Guess::High)).unwrap(); }
+                    KeyCode::Char('s') | KeyCode::Char('S') => { tx.send(ReelCommand::Gamble(Guess::Low)).unwrap(); }
+                    KeyCode::Char('c') | KeyCode::Char('C') => { tx.send(ReelCommand::Collect).unwrap(); }
+                    KeyCode::Char(c) if ui::nudge_key(c, reel_count).is_some() => {
+                        // Q〜Oキー: N番目のリールのナッジ（Shiftとの同時押しは上へ）
+                        let index = ui::nudge_key(c, reel_count).unwrap();
+                        let command = if c.is_ascii_uppercase() {
+                            ReelCommand::NudgeUp(index)
+                        } else {
+                            ReelCommand::NudgeDown(index)
+                        };
+                        tx.send(command).unwrap();
+                    }
+                    KeyCode::Enter => { tx.send(ReelCommand::StopNext).unwrap(); }
                     KeyCode::Left => { tx.send(ReelCommand::Stop(0)).unwrap(); }
-                    KeyCode::Down => { tx.send(ReelCommand::Stop(1)).unwrap(); }
-                    KeyCode::Right => { tx.send(ReelCommand::Stop(2)).unwrap(); }
+                    KeyCode::Down => { tx.send(ReelCommand::Stop(reel_count / 2)).unwrap(); }
+                    KeyCode::Right => { tx.send(ReelCommand::Stop(reel_count - 1)).unwrap(); }
                     KeyCode::Esc => {
                         tx.send(ReelCommand::Exit).unwrap();
                         break;
@@ -300,17 +370,27 @@ fn main() -> io::Result<()> {
     }
 
     // スレッド終了待ち
-    handle.join().unwrap();
+    let slot_machine = handle.join().unwrap();
     terminal::disable_raw_mode()?;
     execute!(stdout(), terminal::Clear(ClearType::All))?;
     execute!(stdout(), cursor::MoveTo(0, 0))?;
     println!("ゲームを終了しました。ありがとうございました！");
+    // 不具合の報告時にこのセッションを再現できるよう、シードとログの場所を案内する
+    println!("シード: {}", slot_machine.seed());
+    println!("セッションログ: {}（gh slot replay {} で再生できます）", log_path.display(), log_path.display());
+    // 設定のあるマシンは答え合わせ
+    if let Some(stats) = slot_machine.stats() {
+        println!();
+        stats.write_reveal(slot_machine.machine(), slot_machine.setting(), &mut stdout())?;
+    }
     Ok(())
 }
 
-// コマンド種別
-enum ReelCommand {
-    StartAll,
-    Stop(usize),
-    Exit,
+/// マシン定義を読み込む
+/// `--machine <ファイル>` が指定されていなければ組み込みの標準マシンを使う
+fn load_machine(path: Option<&Path>) -> Result<MachineDef, String> {
+    match path {
+        Some(path) => MachineDef::load(path).map_err(|err| err.to_string()),
+        None => Ok(MachineDef::builtin()),
+    }
 }
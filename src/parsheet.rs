@@ -0,0 +1,518 @@
+// === パーシート（理論値表）生成モジュール ===
+// リール配列の全停止位置の組み合わせを総当たりし、当選確率とRTPの厳密値を求める
+// シミュレーションと違い誤差がないため、リール配列の調整に使う
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+
+use crate::lottery::Lottery;
+use crate::machine::{MachineDef, SymbolId};
+use crate::paytable::PayRule;
+use crate::cascade;
+use crate::reel::{visible_symbols, Evaluation};
+use crate::scatter;
+use crate::setting::SETTINGS;
+
+/// 出力形式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SheetFormat {
+    Markdown,
+    Csv,
+}
+
+/// 当選役（配当表の行）ごとの集計
+#[derive(Default, Clone, Copy)]
+pub struct OutcomeStat {
+    pub hits: u64,    // 全組み合わせ・全有効ラインでの成立数
+    pub payout: u64,  // 成立時の配当合計（1枚掛け）
+}
+
+/// パーシート
+pub struct ParSheet {
+    pub lines: usize,                                    // 有効ライン数（1ラインあたり1枚掛け）
+    pub combinations: u64,                               // 停止位置の組み合わせ総数
+    pub winning_combinations: u64,                       // 1ライン以上当選する組み合わせ数
+    pub total_payout: u64,                               // 全組み合わせの配当合計
+    pub outcomes: Vec<OutcomeStat>,                      // 当選役ごとの集計（配当表の行の順）
+    pub pay_distribution: BTreeMap<u64, u64>,            // 1回転の配当額 → 組み合わせ数
+    pub free_spin_combinations: u64,                     // フリースピンが始まる組み合わせ数
+}
+
+impl ParSheet {
+    /// 1回転あたりのベット額
+    fn bet(&self) -> u64 {
+        self.lines as u64
+    }
+
+    /// 理論RTP
+    pub fn rtp(&self) -> f64 {
+        self.total_payout as f64 / (self.combinations * self.bet()) as f64
+    }
+
+    /// 理論当選率（1ライン以上当選する確率）
+    pub fn hit_rate(&self) -> f64 {
+        self.winning_combinations as f64 / self.combinations as f64
+    }
+
+    /// フリースピンの突入確率
+    pub fn free_spin_rate(&self) -> f64 {
+        self.free_spin_combinations as f64 / self.combinations as f64
+    }
+
+    /// フリースピン込みの理論RTP
+    /// フリースピンは通常時と同じリールで回り、1回あたり通常時と同じだけ払い戻す。
+    /// 1回転で突入する確率を p、回数を n とすると、リトリガーを含めた1回の突入あたりの
+    /// 平均回数は n / (1 - p·n) になるため、RTP は 通常時のRTP / (1 - p·n) になる
+    /// p·n が1以上のときはフリースピンが終わらない見込みのためNone
+    pub fn rtp_with_free_spins(&self, machine: &MachineDef) -> Option<f64> {
+        let spins = machine.free_spins.as_ref()?.spins as f64;
+        let retrigger = self.free_spin_rate() * spins;
+        (retrigger < 1.0).then(|| self.rtp() / (1.0 - retrigger))
+    }
+
+    /// 当選役の1回転あたりの出現確率（有効ライン全体での期待成立数）
+    fn probability(&self, stat: &OutcomeStat) -> f64 {
+        stat.hits as f64 / self.combinations as f64
+    }
+
+    /// 当選役のRTP寄与
+    fn contribution(&self, stat: &OutcomeStat) -> f64 {
+        stat.payout as f64 / (self.combinations * self.bet()) as f64
+    }
+
+    /// 指定の形式で出力する
+    pub fn write_to(&self, machine: &MachineDef, format: SheetFormat, out: &mut impl Write) -> io::Result<()> {
+        match format {
+            SheetFormat::Markdown => self.write_markdown(machine, out),
+            SheetFormat::Csv => self.write_csv(machine, out),
+        }
+    }
+
+    /// Markdown形式で出力する
+    fn write_markdown(&self, machine: &MachineDef, out: &mut impl Write) -> io::Result<()> {
+        let strips: Vec<String> = machine.reels.iter().map(|strip| strip.len().to_string()).collect();
+        writeln!(out, "# パーシート: {}", machine.name)?;
+        writeln!(out)?;
+        writeln!(out, "| 項目 | 値 |")?;
+        writeln!(out, "|------|----|")?;
+        writeln!(out, "| リール構成 | {} コマ |", strips.join(" × "))?;
+        writeln!(out, "| 組み合わせ総数 | {} |", self.combinations)?;
+        if machine.evaluation == Evaluation::Ways {
+            writeln!(out, "| 判定方式 | {}ウェイ（1枚掛け） |", machine.total_ways())?;
+        } else {
+            writeln!(out, "| 有効ライン数 | {}（1ライン1枚掛け） |", self.lines)?;
+        }
+        if let Some(cascade) = &machine.cascade {
+            writeln!(out, "| 連鎖倍率 | {} |", cascade.label())?;
+        }
+        // 抽選モードのマシンは停止位置が抽選結果に合わせて制御されるため、総当たりのRTP・当選率は実際と違う
+        // （成立するライン数や、ボーナス・リプレイの払い出しで決まる）ので載せない
+        if machine.has_lottery() {
+            writeln!(out, "| 理論RTP | -（内部抽選で決まるため simulate で確認） |")?;
+        } else {
+            writeln!(out, "| 理論RTP | {:.4}% |", self.rtp() * 100.0)?;
+        }
+        if let Some(free_spins) = &machine.free_spins {
+            let rate = self.free_spin_rate();
+            writeln!(
+                out,
+                "| フリースピン突入率 | {:.4}%（1/{:.1}、{}回） |",
+                rate * 100.0,
+                1.0 / rate.max(f64::MIN_POSITIVE),
+                free_spins.spins
+            )?;
+            match self.rtp_with_free_spins(machine) {
+                Some(rtp) => writeln!(out, "| フリースピン込みRTP | {:.4}% |", rtp * 100.0)?,
+                None => writeln!(out, "| フリースピン込みRTP | -（リトリガーで終わらない見込み） |")?,
+            }
+        }
+        if !machine.has_lottery() {
+            writeln!(
+                out,
+                "| 当選率 | {:.4}%（1/{:.2}） |",
+                self.hit_rate() * 100.0,
+                1.0 / self.hit_rate().max(f64::MIN_POSITIVE)
+            )?;
+        }
+        writeln!(out)?;
+
+        // 抽選モードのマシンでは実際の当選確率は抽選テーブルで決まる
+        if machine.has_lottery() {
+            writeln!(out, "## 内部抽選")?;
+            writeln!(out)?;
+            writeln!(out, "停止位置は抽選結果に合わせて最大{}コマの範囲で制御されるため、以下の「当選役」「配当分布」は", machine.slip.max_slip())?;
+            writeln!(out, "停止位置が一様な場合の参考値です。有効ラインが全ライン未満のときは当選確率がライン数に比例して下がります。")?;
+            writeln!(out)?;
+            write_lottery_markdown(machine, |setting| machine.lottery(setting), out)?;
+            writeln!(out)?;
+        }
+
+        // ボーナス中は専用の抽選テーブルで抽選する
+        if !machine.bonuses.is_empty() {
+            writeln!(out, "## ボーナス")?;
+            writeln!(out)?;
+            writeln!(out, "| ボーナス | 開始役 | ゲーム数 | 払い出し上限（1枚掛け換算） |")?;
+            writeln!(out, "|----------|--------|---------:|----------------------------:|")?;
+            for bonus in &machine.bonuses {
+                writeln!(
+                    out,
+                    "| {} | {} | {} | {} |",
+                    bonus.kind,
+                    vec![machine.glyph(bonus.symbol); bonus.count].join(" "),
+                    bonus.games,
+                    bonus.payout_cap.map_or("-".to_string(), |cap| cap.to_string())
+                )?;
+            }
+            writeln!(out)?;
+            for bonus in &machine.bonuses {
+                writeln!(out, "### {} 中の抽選", bonus.kind)?;
+                writeln!(out)?;
+                write_lottery_markdown(machine, |setting| bonus.lottery(setting), out)?;
+                writeln!(out)?;
+            }
+        }
+
+        writeln!(out, "## 当選役")?;
+        writeln!(out)?;
+        writeln!(out, "| 役 | 倍率 | 成立数 | 確率 | 出現間隔 | RTP寄与 |")?;
+        writeln!(out, "|----|-----:|-------:|-----:|---------:|--------:|")?;
+        for (entry, stat) in machine.paytable.entries().iter().zip(&self.outcomes) {
+            let probability = self.probability(stat);
+            // スキャッタは「どこでもN個」で総ベットの倍数を払う
+            let label = if machine.symbols[entry.symbol].scatter {
+                format!("{}（総ベット倍）", machine.pay_label(entry))
+            } else {
+                machine.pay_label(entry)
+            };
+            let cycle = if stat.hits == 0 {
+                "-".to_string()
+            } else {
+                format!("1/{:.1}", 1.0 / probability)
+            };
+            writeln!(
+                out,
+                "| {} | {} | {} | {:.6}% | {} | {:.4}% |",
+                label,
+                entry.multiplier,
+                stat.hits,
+                probability * 100.0,
+                cycle,
+                self.contribution(stat) * 100.0
+            )?;
+        }
+        writeln!(out)?;
+
+        writeln!(out, "## 1回転の配当分布")?;
+        writeln!(out)?;
+        writeln!(out, "| 配当 | 組み合わせ数 | 確率 |")?;
+        writeln!(out, "|-----:|-------------:|-----:|")?;
+        for (&pay, &count) in &self.pay_distribution {
+            writeln!(
+                out,
+                "| {} | {} | {:.6}% |",
+                pay,
+                count,
+                count as f64 / self.combinations as f64 * 100.0
+            )?;
+        }
+        Ok(())
+    }
+
+    /// CSV形式で出力する（当選役ごとに1行、最終行に合計）
+    fn write_csv(&self, machine: &MachineDef, out: &mut impl Write) -> io::Result<()> {
+        writeln!(out, "symbol,name,count,rule,multiplier,hits,combinations,probability,rtp_contribution")?;
+        for (entry, stat) in machine.paytable.entries().iter().zip(&self.outcomes) {
+            // グループの行は symbol 列にグループ名を出す
+            let symbol = match entry.group {
+                Some(group) => &machine.paytable.groups()[group].name,
+                None => machine.glyph(entry.symbol),
+            };
+            let rule = match entry.rule {
+                _ if machine.symbols[entry.symbol].scatter => "scatter",
+                PayRule::Left => "left",
+                PayRule::Anywhere => "anywhere",
+            };
+            writeln!(
+                out,
+                "{},{},{},{},{},{},{},{:.10},{:.10}",
+                csv_field(symbol),
+                csv_field(machine.pay_name(entry)),
+                entry.count,
+                rule,
+                entry.multiplier,
+                stat.hits,
+                self.combinations,
+                self.probability(stat),
+                self.contribution(stat)
+            )?;
+        }
+        // 抽選モードのマシンは合計の確率とRTPを空欄にする（停止位置が一様でないため）
+        if machine.has_lottery() {
+            writeln!(out, "TOTAL,,,,,{},{},,", self.winning_combinations, self.combinations)?;
+        } else {
+            writeln!(
+                out,
+                "TOTAL,,,,,{},{},{:.10},{:.10}",
+                self.winning_combinations,
+                self.combinations,
+                self.hit_rate(),
+                self.rtp()
+            )?;
+        }
+        if let Some(rtp) = self.rtp_with_free_spins(machine) {
+            writeln!(
+                out,
+                "FREE_SPINS,,,,,{},{},{:.10},{:.10}",
+                self.free_spin_combinations,
+                self.combinations,
+                self.free_spin_rate(),
+                rtp
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// 抽選テーブルをMarkdownの表で出力する
+/// 設定のあるマシンは設定ごとの出現間隔、ないマシンは重みと確率を並べる
+///
+/// # 引数
+/// * `lottery_of` - 設定（1〜6）から抽選テーブルを引く関数
+fn write_lottery_markdown<'a>(
+    machine: &MachineDef,
+    lottery_of: impl Fn(usize) -> Option<&'a Lottery>,
+    out: &mut impl Write,
+) -> io::Result<()> {
+    let Some(lottery) = lottery_of(1) else {
+        return Ok(());
+    };
+    if machine.has_settings() {
+        // 設定ごとの出現間隔（全ライン有効時）
+        let settings: Vec<usize> = (1..=SETTINGS).collect();
+        let header: Vec<String> = settings.iter().map(|setting| format!("設定{}", setting)).collect();
+        writeln!(out, "| 結果 | {} |", header.join(" | "))?;
+        writeln!(out, "|------|{}", "-----:|".repeat(SETTINGS))?;
+        for (index, entry) in lottery.entries().iter().enumerate() {
+            let cells: Vec<String> = settings
+                .iter()
+                .filter_map(|&setting| lottery_of(setting))
+                .map(|table| {
+                    let probability = table.probability(&table.entries()[index]);
+                    if probability > 0.0 {
+                        format!("1/{:.1}", 1.0 / probability)
+                    } else {
+                        "-".to_string()
+                    }
+                })
+                .collect();
+            writeln!(out, "| {} | {} |", machine.outcome_label(entry.outcome), cells.join(" | "))?;
+        }
+    } else {
+        writeln!(out, "| 結果 | 重み | 確率（全ライン） | 出現間隔 |")?;
+        writeln!(out, "|------|-----:|-----------------:|---------:|")?;
+        for entry in lottery.entries() {
+            let probability = lottery.probability(entry);
+            writeln!(
+                out,
+                "| {} | {} | {:.4}% | 1/{:.1} |",
+                machine.outcome_label(entry.outcome),
+                entry.weight,
+                probability * 100.0,
+                1.0 / probability
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// CSVの値をエスケープする（カンマ・引用符を含む場合は引用符で囲む）
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// スキャッタの数をリールごとに前もって数えた表
+/// スキャッタの配当とフリースピンの突入は盤面全体の数だけで決まるため、
+/// 組み合わせごとに盤面を数え直さず、リールごとの数を足すだけで判定する
+struct ScatterCounts {
+    per_stop: Vec<Vec<Vec<usize>>>,        // [リール][停止位置][シンボル] → 見えている数（シンボルはスキャッタ・フリースピンの対象）
+    pays: Vec<Vec<Option<(usize, u64)>>>,  // [シンボル][盤面全体の数] → (配当表の行, 配当)
+    free_spins: Option<(usize, usize)>,    // フリースピンの対象の添字と、突入に必要な数
+}
+
+impl ScatterCounts {
+    /// マシン定義から表を作る
+    ///
+    /// # 引数
+    /// * `total_bet` - 総ベット（スキャッタの配当は総ベットの倍数）
+    fn new(machine: &MachineDef, total_bet: u64) -> Self {
+        let mut symbols: Vec<SymbolId> = (0..machine.symbols.len()).filter(|&symbol| machine.symbols[symbol].scatter).collect();
+        let free_spins = machine.free_spins.as_ref().map(|def| match symbols.iter().position(|&symbol| symbol == def.symbol) {
+            Some(index) => (index, def.count),
+            None => {
+                symbols.push(def.symbol);
+                (symbols.len() - 1, def.count)
+            }
+        });
+        let per_stop = machine
+            .reels
+            .iter()
+            .map(|strip| {
+                (0..strip.len())
+                    .map(|stop| {
+                        let visible = visible_symbols(strip, stop, machine.rows);
+                        symbols
+                            .iter()
+                            .map(|&symbol| visible.iter().filter(|&&cell| cell == symbol).count())
+                            .collect()
+                    })
+                    .collect()
+            })
+            .collect();
+        // 盤面全体の数ごとの配当（evaluate_scatters と同じく、出た数以下で最も多い数の行）
+        let cells = machine.reels.len() * machine.rows;
+        let pays = symbols
+            .iter()
+            .map(|&symbol| {
+                (0..=cells)
+                    .map(|count| {
+                        let window = vec![vec![symbol; count]];
+                        scatter::evaluate_scatters(&window, machine, total_bet)
+                            .into_iter()
+                            .find(|win| win.symbol == symbol)
+                            .map(|win| (win.entry, win.payout))
+                    })
+                    .collect()
+            })
+            .collect();
+        Self { per_stop, pays, free_spins }
+    }
+}
+
+/// 全停止位置の組み合わせを総当たりしてパーシートを作成する
+///
+/// # 引数
+/// * `machine` - 対象のマシン定義
+/// * `lines` - 有効ライン数
+pub fn compute(machine: &MachineDef, lines: usize) -> ParSheet {
+    compute_with_progress(machine, lines, |_, _| {})
+}
+
+/// 全停止位置の組み合わせを総当たりしてパーシートを作成し、途中経過を知らせる
+/// 組み合わせ総数はリールのコマ数の積で、5リール30コマなら2430万通りになり時間がかかるため、
+/// 先頭のリールが1コマ進むたびに progress（調べた組み合わせ数, 組み合わせ総数）を呼ぶ
+///
+/// # 引数
+/// * `machine` - 対象のマシン定義
+/// * `lines` - 有効ライン数
+/// * `progress` - 途中経過を受け取る関数
+pub fn compute_with_progress(machine: &MachineDef, lines: usize, mut progress: impl FnMut(u64, u64)) -> ParSheet {
+    let paylines = machine.paylines.active(lines);
+    let mut sheet = ParSheet {
+        // ウェイ判定はペイラインを使わず、1ライン分のベットで回す
+        lines: if machine.evaluation == Evaluation::Ways { 1 } else { paylines.len() },
+        combinations: 0,
+        winning_combinations: 0,
+        total_payout: 0,
+        outcomes: vec![OutcomeStat::default(); machine.paytable.entries().len()],
+        pay_distribution: BTreeMap::new(),
+        free_spin_combinations: 0,
+    };
+
+    let scatters = ScatterCounts::new(machine, sheet.bet());
+    let total: u64 = machine.reels.iter().map(|strip| strip.len() as u64).product();
+
+    // 各リールの停止位置を「桁」とみなし、オドメーターのように1つずつ進めて全通りを列挙する
+    let mut stops = vec![0usize; machine.reels.len()];
+    let mut window: Vec<Vec<SymbolId>> = machine
+        .reels
+        .iter()
+        .map(|strip| visible_symbols(strip, 0, machine.rows))
+        .collect();
+    loop {
+        // カスケードのマシンは連鎖した分の当選も含める
+        let wins = cascade::evaluate_all(machine, &window, &stops, paylines, 1);
+        let mut pay: u64 = wins.iter().map(|win| win.payout).sum();
+        for win in &wins {
+            let stat = &mut sheet.outcomes[win.entry];
+            stat.hits += 1;
+            stat.payout += win.payout;
+        }
+        // スキャッタは成立した配当表の行（出た数以下で最も多い数）ごとに集計する
+        // 盤面全体の数はリールごとに前もって数えた数の合計（組み合わせごとに盤面を数え直さない）
+        for (index, pays) in scatters.pays.iter().enumerate() {
+            let count: usize = stops.iter().enumerate().map(|(reel, &stop)| scatters.per_stop[reel][stop][index]).sum();
+            if let Some((entry, payout)) = pays[count] {
+                let stat = &mut sheet.outcomes[entry];
+                stat.hits += 1;
+                stat.payout += payout;
+                pay += payout;
+            }
+            if scatters.free_spins.is_some_and(|(symbol, needed)| symbol == index && count >= needed) {
+                sheet.free_spin_combinations += 1;
+            }
+        }
+        sheet.combinations += 1;
+        sheet.total_payout += pay;
+        if pay > 0 {
+            sheet.winning_combinations += 1;
+        }
+        *sheet.pay_distribution.entry(pay).or_default() += 1;
+
+        // 次の組み合わせへ（右端のリールから繰り上げ）
+        let mut reel = machine.reels.len();
+        loop {
+            if reel == 0 {
+                return sheet;  // 全リールが一周したら終了
+            }
+            reel -= 1;
+            stops[reel] = (stops[reel] + 1) % machine.reels[reel].len();
+            window[reel] = visible_symbols(&machine.reels[reel], stops[reel], machine.rows);
+            if reel == 0 {
+                progress(sheet.combinations, total);
+            }
+            if stops[reel] != 0 {
+                break;  // 繰り上がりなし
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builtin_machine_counts_every_combination_exactly() {
+        let machine = MachineDef::builtin();
+        let sheet = compute(&machine, 7);
+        assert_eq!(sheet.combinations, 21 * 21 * 21);
+        assert_eq!(sheet.winning_combinations, 2155);
+        // 300×14 + 200×28 + 200×28 + 100×70 + 15×875 + 10×1400
+        assert_eq!(sheet.total_payout, 49_525);
+        assert_eq!(sheet.free_spin_combinations, 0);
+        let hits: Vec<u64> = sheet.outcomes.iter().map(|stat| stat.hits).collect();
+        assert_eq!(hits, vec![14, 28, 28, 70, 0, 875, 1400]);
+        assert_eq!(sheet.pay_distribution[&0], 7106);
+        assert_eq!(sheet.pay_distribution.values().sum::<u64>(), sheet.combinations);
+    }
+
+    #[test]
+    fn line_hits_are_the_product_of_symbol_counts() {
+        // 1本のラインに揃う停止位置の数は、各リールにあるそのシンボルの数の積
+        let machine = MachineDef::builtin();
+        for lines in [1, 7] {
+            let sheet = compute(&machine, lines);
+            for (entry, stat) in machine.paytable.entries().iter().zip(&sheet.outcomes) {
+                let per_line: u64 = machine
+                    .reels
+                    .iter()
+                    .map(|strip| strip.iter().filter(|&&symbol| symbol == entry.symbol).count() as u64)
+                    .product();
+                assert_eq!(stat.hits, per_line * lines as u64, "{}（{}ライン）", machine.pay_label(entry), lines);
+            }
+        }
+    }
+}
@@ -0,0 +1,62 @@
+// === 配当表モジュール ===
+// シンボル（またはシンボルのグループ）と揃った数の組み合わせごとに、1枚掛けあたりの配当倍率を定義する
+// 揃い方は「左のリールから続けて揃う」か「ライン上のどこでもよい」かを行ごとに選べる
+use crate::machine::SymbolId;
+
+/// 揃い方の決まり
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PayRule {
+    /// 左のリールから続けて count 個揃う（count がリール数ならライン全体）
+    Left,
+    /// ライン上のどこでもよいので count 個以上ある
+    Anywhere,
+}
+
+/// シンボルのグループ（「フルーツならどれでも」のような役に使う）
+#[derive(Debug, Clone)]
+pub struct SymbolGroup {
+    pub name: String,            // グループの名前（配当表などで使用）
+    pub symbols: Vec<SymbolId>,  // グループに含まれるシンボル
+}
+
+/// 配当表の1行分（どのシンボルがどう揃えば何倍か）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PayEntry {
+    pub symbol: SymbolId,      // 対象シンボル（グループの行ではグループの先頭のシンボル）
+    pub group: Option<usize>,  // 対象のグループ（Paytable::groups の添字、シンボル単独の行はNone）
+    pub count: usize,          // 揃った数
+    pub rule: PayRule,         // 揃い方
+    pub multiplier: u64,       // 1枚掛けあたりの配当倍率
+}
+
+/// 配当表
+/// 内容はマシン定義ファイルの [[paytable]] と [[groups]] から作られる
+pub struct Paytable {
+    entries: Vec<PayEntry>,
+    groups: Vec<SymbolGroup>,
+}
+
+impl Paytable {
+    /// 配当表の行とシンボルのグループから配当表を作成
+    pub fn new(entries: Vec<PayEntry>, groups: Vec<SymbolGroup>) -> Self {
+        Self { entries, groups }
+    }
+
+    /// 行の対象にシンボルが含まれるか（グループの行はグループのどれか、それ以外は対象シンボルそのもの）
+    pub fn covers(&self, entry: &PayEntry, symbol: SymbolId) -> bool {
+        match entry.group {
+            Some(group) => self.groups[group].symbols.contains(&symbol),
+            None => entry.symbol == symbol,
+        }
+    }
+
+    /// 配当表の全行（ヘルプ画面の表示用）
+    pub fn entries(&self) -> &[PayEntry] {
+        &self.entries
+    }
+
+    /// シンボルのグループ
+    pub fn groups(&self) -> &[SymbolGroup] {
+        &self.groups
+    }
+}
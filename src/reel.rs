@@ -1,192 +1,969 @@
 // === 外部ライブラリのインポート ===
-use rand::Rng;                        // ランダム数生成（リールの初期位置決定用）
-use std::sync::{Arc, Mutex};          // スレッドセーフな共有データ用（複数スレッドで安全にデータを共有）
 use std::time::Duration;              // 時間間隔の指定用
-use tokio::time::sleep;               // 非同期での待機処理用
-
-// === スロットマシンの基本設定 ===
-pub const REEL_SIZE: usize = 21;      // 各リールのシンボル総数（21個の絵文字）
-pub const DISPLAY_SIZE: usize = 3;    // 画面に表示される縦のシンボル数（3個）
-
-// === 各リールのシンボル配列定義 ===
-// 注意：各リールは異なるシンボル配列を持つため、当選確率が調整されています
-
-// リール1のシンボル（左リール）
-// 配列のインデックス0〜20に対応する21個の絵文字
-pub const REEL1_SYMBOLS: [&str; REEL_SIZE] = [
-    "⭐", "💯", "🏀", "🍀", "🏀", "🍀", "🎩",     // インデックス 0〜6
-    "🍒", "🍀", "🏀", "🍀", "💯", "⚪", "🍀",     // インデックス 7〜13
-    "🏀", "🍀", "🍒", "🎩", "🍀", "🏀", "🍀"      // インデックス 14〜20
-];
-
-// リール2のシンボル（中リール）
-// 左リールとは異なる配列で、ゲームバランスを調整
-pub const REEL2_SYMBOLS: [&str; REEL_SIZE] = [
-    "🏀", "💯", "🍀", "🍒", "🏀", "⭐", "🍀",     // インデックス 0〜6
-    "🍒", "🏀", "🎩", "🍀", "🍒", "🏀", "⭐",     // インデックス 7〜13
-    "🍀", "🍒", "🏀", "🎩", "🍀", "🍒", "⚪"      // インデックス 14〜20
-];
-
-// リール3のシンボル（右リール）
-// 右リール専用の配列で、当選ラインの成立確率を制御
-pub const REEL3_SYMBOLS: [&str; REEL_SIZE] = [
-    "🍀", "💯", "🎩", "⭐", "🏀", "🍀", "⚪",     // インデックス 0〜6
-    "⭐", "🏀", "🍀", "⚪", "⭐", "🏀", "🍀",     // インデックス 7〜13
-    "⚪", "⭐", "🏀", "🍀", "⚪", "⭐", "🏀"      // インデックス 14〜20
-];
+use crate::lottery::{self, Outcome};  // 抽選結果と、その結果を成立させられるかの判定
+use crate::machine::{MachineDef, SymbolDef, SymbolId}; // リール配列・表示段数・回転速度・シンボルの定義
+use crate::paytable::{PayEntry, PayRule, Paytable}; // 当選時の配当倍率を引く配当表
 
 // === Reel構造体の定義 ===
-// #[derive(Clone)] により、この構造体はコピー（クローン）が可能になる
-// 複数のスレッドで同じリールデータを安全に共有するために必要
-#[derive(Clone)]
+// リールの状態は1つの値（ReelState）で持ち、ゲームループのスレッドだけが書き換える
+// 画面に見せる位置は状態と時刻から計算するため、共有するロックや別スレッドでの更新は持たない
 pub struct Reel {
-    // Arc<Mutex<T>>について：
-    // Arc = Atomically Reference Counted：複数スレッドで安全に共有可能
-    // Mutex = Mutual Exclusion：同時アクセスを防ぎ、データ競合を回避
-    
-    pub position: Arc<Mutex<usize>>,        // 現在のリール位置（0〜20の範囲）
-    pub is_spinning: Arc<Mutex<bool>>,      // 回転中かどうかのフラグ
-    pub stop_requested: Arc<Mutex<bool>>,   // 停止要求が出されたかのフラグ
-    pub reel_id: usize,                     // リールのID（0=左, 1=中, 2=右）
+    pub reel_id: usize,       // リールのID（左から0, 1, 2…）
+    state: ReelState,         // 回転中 / 停止（すべりを含む）
+    strip: Vec<SymbolId>,     // このリールのシンボル配列（マシン定義から取得）
+    rows: usize,              // 画面に表示される縦のシンボル数
+    step: Duration,           // 1コマ進む間隔
+}
+
+/// リールの状態
+/// 回転中・停止のどちらでも、画面の位置はこの値と時刻だけで決まる
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ReelState {
+    /// 回転中（回転開始時の位置と時刻）
+    Spinning { origin: usize, started_ms: u64 },
+    /// 停止（停止位置と、停止ボタンを押した位置・すべりコマ数・押した時刻）
+    Stopped { position: usize, pressed: usize, slip: usize, pressed_ms: u64 },
+}
+
+/// ある時刻のリール1本の見え方（1フレーム分のスナップショット）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReelFrame {
+    pub position: usize,  // 停止位置（回転中は回転開始時の位置）
+    pub display: usize,   // 画面に表示する位置（回転中・すべり中は停止位置と異なる）
+    pub spinning: bool,   // 回転中かどうか（停止ボタンを押した時点で停止扱い）
+    pub sliding: bool,    // 停止位置が決まった後、まだすべっている途中かどうか
+}
+
+/// 全リールの1フレーム分のスナップショット
+/// 描画は1フレームにつき1回だけ全リールを同じ時刻で読み、その後はこの値だけを見る
+/// （リールごとに別の時刻の位置を描いたり、描画の途中で状態が変わったりしない）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frame {
+    pub at_ms: u64,             // スナップショットの時刻（セッション開始からのミリ秒）
+    pub reels: Vec<ReelFrame>,  // 各リールの見え方（左から）
+}
+
+impl Frame {
+    /// 全リールを同じ時刻で読んでスナップショットを作る
+    pub fn capture(reels: &[Reel], at_ms: u64) -> Self {
+        Self {
+            at_ms,
+            reels: reels.iter().map(|reel| reel.frame(at_ms)).collect(),
+        }
+    }
+
+    /// 回転中のリールがあるかどうか
+    pub fn any_spinning(&self) -> bool {
+        self.reels.iter().any(|reel| reel.spinning)
+    }
+
+    /// すべり中のリールがあるかどうか
+    pub fn any_sliding(&self) -> bool {
+        self.reels.iter().any(|reel| reel.sliding)
+    }
+
+    /// 回転中またはすべり中のリールがあるかどうか（画面が動いているか）
+    pub fn is_moving(&self) -> bool {
+        self.any_spinning() || self.any_sliding()
+    }
 }
 
 impl Reel {
     /// 新しいリールインスタンスを作成
     /// 
     /// # 引数
-    /// * `reel_id` - リールのID（0=左, 1=中, 2=右）
+    /// * `reel_id` - リールのID（左から0, 1, 2…）
+    /// * `machine` - リール配列・表示段数・回転速度を持つマシン定義
+    /// * `position` - 開始位置（呼び出し側がセッションの乱数で決める）
     /// 
     /// # 戻り値
-    /// 初期化されたReelインスタンス（ランダムな開始位置を持つ）
-    pub fn new(reel_id: usize) -> Self {
-        let mut rng = rand::thread_rng();  // ランダム数生成器を取得
+    /// 初期化されたReelインスタンス
+    pub fn new(reel_id: usize, machine: &MachineDef, position: usize) -> Self {
+        let strip = machine.reels[reel_id].clone();
+        let position = position % strip.len();
         Self {
-            // Arc::new(Mutex::new(値)) でスレッドセーフな共有データを作成
-            position: Arc::new(Mutex::new(rng.gen_range(0..REEL_SIZE))), // 0〜20のランダム位置
-            is_spinning: Arc::new(Mutex::new(false)),                    // 初期状態は停止
-            stop_requested: Arc::new(Mutex::new(false)),                 // 停止要求なし
             reel_id,                                                     // リールIDを保存
+            state: ReelState::Stopped { position, pressed: position, slip: 0, pressed_ms: 0 },  // 初期状態は停止
+            strip,
+            rows: machine.rows,
+            step: machine.spin_step,
         }
     }
 
     /// リールの回転を開始
-    /// この関数は複数スレッドから安全に呼び出し可能
-    pub fn start_spinning(&self) {
-        // .lock().unwrap() でMutexロックを取得（他スレッドのアクセスをブロック）
-        let mut is_spinning = self.is_spinning.lock().unwrap();
-        let mut stop_requested = self.stop_requested.lock().unwrap();
-        
-        // デリファレンス演算子 * で実際の値を変更
-        *is_spinning = true;        // 回転開始フラグをON
-        *stop_requested = false;    // 停止要求をリセット
-    } // スコープを抜ける時にMutexロックが自動的に解放される
-
-    /// リールの停止要求を発行
-    /// 実際の停止はspin_loop内で処理される
-    pub fn request_stop(&self) {
-        let mut stop_requested = self.stop_requested.lock().unwrap();
-        *stop_requested = true;  // 停止要求フラグをON
+    /// 回転中のリールは何もしない（回転の起点を変えない）
+    ///
+    /// # 引数
+    /// * `now_ms` - 回転開始の時刻（セッション開始からのミリ秒）
+    pub fn start_spinning(&mut self, now_ms: u64) {
+        if let ReelState::Stopped { position, .. } = self.state {
+            // 回転の起点を記録（すべり途中の表示は打ち切る）
+            self.state = ReelState::Spinning { origin: position, started_ms: now_ms };
+        }
+    }
+
+    /// 指定した位置でリールを停止する
+    /// 停止位置は呼び出し側が「停止ボタンが押された時刻」から決めるため、
+    /// 同じ時刻の入力を再生すれば必ず同じ位置に止まる（リプレイ用）
+    /// 停止位置は即座に確定し、画面上は押した位置から停止位置まで減速しながらすべって見える
+    ///
+    /// # 引数
+    /// * `position` - 停止位置（押した位置から最大すべりコマ数までの範囲）
+    /// * `now_ms` - 停止ボタンが押された時刻（セッション開始からのミリ秒）
+    pub fn stop_at(&mut self, position: usize, now_ms: u64) {
+        if !self.is_spinning() {
+            return;  // 既に停止している
+        }
+        let len = self.strip.len();
+        let pressed = self.position_at(now_ms);
+        let position = position % len;
+        self.state = ReelState::Stopped {
+            position,
+            pressed,
+            slip: (position + len - pressed) % len,
+            pressed_ms: now_ms,
+        };
+    }
+
+    /// 停止中のリールを1コマだけ動かす（ナッジ）
+    /// すべりの演出はなく、表示もすぐに新しい位置になる
+    ///
+    /// # 引数
+    /// * `up` - trueならシンボルが1段上がる（リール配列の次の位置）、falseなら1段下がる
+    pub fn nudge(&mut self, up: bool) {
+        let ReelState::Stopped { position, .. } = self.state else {
+            return;  // 回転中は動かせない
+        };
+        let len = self.strip.len();
+        let position = if up { (position + 1) % len } else { (position + len - 1) % len };
+        // すべり終わった状態にしておく
+        self.state = ReelState::Stopped { position, pressed: position, slip: 0, pressed_ms: 0 };
+    }
+
+    /// 停止位置（回転中は回転開始時の位置）
+    pub fn position(&self) -> usize {
+        match self.state {
+            ReelState::Spinning { origin, .. } => origin,
+            ReelState::Stopped { position, .. } => position,
+        }
+    }
+
+    /// 回転中のリールが指定時刻に表示している位置を計算する
+    /// 回転開始位置から「経過時間 ÷ 1コマの間隔」だけ進んだ位置（停止中は停止位置）
+    ///
+    /// # 引数
+    /// * `now_ms` - 時刻（セッション開始からのミリ秒）
+    pub fn position_at(&self, now_ms: u64) -> usize {
+        match self.state {
+            ReelState::Spinning { origin, started_ms } => {
+                let steps = now_ms.saturating_sub(started_ms) / self.step_ms();
+                (origin + steps as usize) % self.strip.len()
+            }
+            ReelState::Stopped { position, .. } => position,
+        }
+    }
+
+    /// 指定時刻に画面に表示する位置を計算する
+    /// 停止後のすべり中は、1コマ進むごとに間隔を延ばし（通常の1.5倍から0.5倍ずつ）、減速して止まるように見せる
+    fn display_at(&self, now_ms: u64) -> usize {
+        let ReelState::Stopped { pressed, slip, pressed_ms, .. } = self.state else {
+            return self.position_at(now_ms);
+        };
+        let mut elapsed = now_ms.saturating_sub(pressed_ms);
+        let mut moved = 0;
+        while moved < slip {
+            let interval = self.step_ms() * (moved as u64 + 3) / 2;
+            if elapsed < interval {
+                break;
+            }
+            elapsed -= interval;
+            moved += 1;
+        }
+        (pressed + moved) % self.strip.len()
+    }
+
+    /// 1コマ進む間隔（ミリ秒）
+    fn step_ms(&self) -> u64 {
+        (self.step.as_millis() as u64).max(1)
     }
 
     /// リールが現在回転中かどうかを確認
+    /// 停止ボタンが押された時点で停止扱いになる（すべり中も含む）
     /// 
     /// # 戻り値
     /// true: 回転中, false: 停止中
     pub fn is_spinning(&self) -> bool {
-        *self.is_spinning.lock().unwrap()  // 現在の回転状態を返す
+        matches!(self.state, ReelState::Spinning { .. })
     }
 
-    /// 画面に表示される3つのシンボルを取得
-    /// リールの現在位置から連続する3つのシンボルを返す
+    /// 指定時刻のこのリールの見え方
+    pub fn frame(&self, now_ms: u64) -> ReelFrame {
+        let display = self.display_at(now_ms);
+        let spinning = self.is_spinning();
+        ReelFrame {
+            position: self.position(),
+            display,
+            spinning,
+            sliding: !spinning && display != self.position(),
+        }
+    }
+
+    /// 画面に見えるシンボルを取得（当選判定用、停止位置から表示段数分）
     /// 
     /// # 戻り値
     /// [上段, 中段, 下段] の順でシンボルが格納された配列
-    pub fn get_visible_symbols(&self) -> [&'static str; DISPLAY_SIZE] {
-        let position = *self.position.lock().unwrap();  // 現在のリール位置を取得
-        
-        // リールIDに応じて対応するシンボル配列を選択
-        let symbols = match self.reel_id {
-            0 => &REEL1_SYMBOLS,    // 左リール
-            1 => &REEL2_SYMBOLS,    // 中リール  
-            2 => &REEL3_SYMBOLS,    // 右リール
-            _ => &REEL1_SYMBOLS,    // 想定外のIDの場合はデフォルトで左リール
+    pub fn get_visible_symbols(&self) -> Vec<SymbolId> {
+        visible_symbols(&self.strip, self.position(), self.rows)
+    }
+
+    /// 指定位置で見えるシンボルを取得（描画用、ReelFrame::display を渡す）
+    pub fn symbols_at(&self, position: usize) -> Vec<SymbolId> {
+        visible_symbols(&self.strip, position, self.rows)
+    }
+}
+
+/// リール配列の指定位置から見える表示段数分のシンボルを取得
+/// 画面表示とシミュレーションの両方で同じ並びを使うための共通処理
+///
+/// # 引数
+/// * `strip` - リールのシンボル配列
+/// * `position` - 停止位置（上段に来るシンボルの添字）
+/// * `rows` - 表示段数
+pub fn visible_symbols(strip: &[SymbolId], position: usize, rows: usize) -> Vec<SymbolId> {
+    // 現在位置から連続するシンボルを返す
+    // % strip.len() で配列の境界を超えた場合に先頭に戻る（循環）
+    (0..rows)
+        .map(|row| strip[(position + row) % strip.len()])
+        .collect()
+}
+
+/// ペイラインの集合
+/// ゲーム中に有効ライン数を切り替えられるよう、実行時にラインを保持する
+/// 各ラインは「リールごとに参照する段」の並び（0=上段, 1=中段, 2=下段）
+pub struct PaylineSet {
+    lines: Vec<Vec<usize>>,  // 全ペイライン（有効になる順に並ぶ）
+}
+
+impl PaylineSet {
+    /// ペイラインの一覧からペイライン集合を作成
+    pub fn new(lines: Vec<Vec<usize>>) -> Self {
+        Self { lines }
+    }
+
+    /// 全ペイライン（有効・無効を問わない）
+    pub fn all(&self) -> &[Vec<usize>] {
+        &self.lines
+    }
+
+    /// 有効ライン数に応じた先頭からのペイライン
+    pub fn active(&self, count: usize) -> &[Vec<usize>] {
+        &self.lines[..count.min(self.lines.len())]
+    }
+}
+
+/// 当選判定の方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Evaluation {
+    /// ペイライン上に揃ったかで判定する
+    Lines,
+    /// 左のリールから隣り合うリールに続けて揃ったかを段を問わず判定する（3×3なら27ウェイ、5×3なら243ウェイ）
+    Ways,
+}
+
+/// マシンの判定方式に従って盤面の当選判定を行う
+/// ウェイ判定のマシンでは paylines は使わない
+///
+/// # 引数
+/// * `machine` - マシン定義（判定方式・シンボル・配当表）
+/// * `reel_symbols` - リールごとの表示シンボル（[リール][段]）
+/// * `paylines` - 判定する有効ライン（無効なラインは渡さない）
+/// * `coins_per_line` - 1ラインあたりの掛け枚数
+///
+/// # 戻り値
+/// 配当のある当選の一覧
+pub fn evaluate(
+    machine: &MachineDef,
+    reel_symbols: &[Vec<SymbolId>],
+    paylines: &[Vec<usize>],
+    coins_per_line: u64,
+) -> Vec<LineWin> {
+    match machine.evaluation {
+        Evaluation::Lines => evaluate_window(reel_symbols, paylines, &machine.symbols, &machine.paytable, coins_per_line),
+        Evaluation::Ways => evaluate_ways(reel_symbols, &machine.symbols, &machine.paytable, coins_per_line),
+    }
+}
+
+/// 1ライン分（ウェイ判定では1つの役の全ウェイ分）の当選情報
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LineWin {
+    pub line: Option<usize>,    // 当選したラインのインデックス（ペイラインの添字、ウェイ判定ではNone）
+    pub entry: usize,           // 成立した配当表の行（Paytable::entries の添字）
+    pub symbol: SymbolId,       // 揃ったシンボル（グループの役ではグループの先頭のシンボル）
+    pub count: usize,           // 揃った数（配当表の行の数）
+    pub payout: u64,            // 配当クレジット（倍率 × ワイルド倍率 × 1ラインあたりの掛け枚数）
+    pub multiplier: u64,        // ライン上のワイルドによる倍率（ワイルドなし・ワイルドだけの場合は1）
+    pub ways: u64,              // 成立したウェイ数（ライン判定では1）
+}
+
+/// 盤面（リールごとの表示シンボル）に対して有効ラインの当選判定を行う
+/// 画面を持たないシミュレーションからも同じ判定を使う
+/// 配当表の行ごとに揃い方（左から・ライン上のどこでも）と対象（シンボル・グループ）を調べ、
+/// 1ラインにつき成立した行のうち最も配当の高いもの（同じなら配当表で先の行）だけを払う
+/// ワイルドは他のシンボルの代わりになり、代わりになったワイルドの倍率は掛け合わせて配当に乗せる
+/// ワイルドだけの並びはワイルド自身の役としてのみ成立する（先頭のワイルドの行を引き、倍率は乗せない）
+/// ワイルドはスキャッタの代わりにはならない
+///
+/// # 引数
+/// * `reel_symbols` - リールごとの表示シンボル（[リール][段]）
+/// * `paylines` - 判定する有効ライン（無効なラインは渡さない）
+/// * `symbols` - シンボルの定義（ワイルドの判定に使う）
+/// * `paytable` - 配当倍率を引く配当表
+/// * `coins_per_line` - 1ラインあたりの掛け枚数
+///
+/// # 戻り値
+/// 配当のある当選ラインの一覧
+pub fn evaluate_window(
+    reel_symbols: &[Vec<SymbolId>],
+    paylines: &[Vec<usize>],
+    symbols: &[SymbolDef],
+    paytable: &Paytable,
+    coins_per_line: u64,
+) -> Vec<LineWin> {
+    let mut wins = Vec::new();
+    let mut line_symbols: Vec<SymbolId> = Vec::with_capacity(reel_symbols.len());
+
+    for (line_index, line) in paylines.iter().enumerate() {
+        line_symbols.clear();
+        line_symbols.extend(line.iter().enumerate().map(|(reel_index, &row)| reel_symbols[reel_index][row]));
+
+        let mut best: Option<LineWin> = None;
+        for (entry_index, entry) in paytable.entries().iter().enumerate() {
+            // スキャッタはライン上では揃わない（配当は scatter::evaluate_scatters で盤面全体から数える）
+            if symbols[entry.symbol].scatter {
+                continue;
+            }
+            let Some(multiplier) = line_multiplier(&line_symbols, entry, symbols, paytable) else {
+                continue;
+            };
+            let payout = entry.multiplier * multiplier * coins_per_line;
+            if best.as_ref().is_some_and(|best| best.payout >= payout) {
+                continue;
+            }
+            best = Some(LineWin {
+                line: Some(line_index),
+                entry: entry_index,
+                symbol: entry.symbol,
+                count: entry.count,
+                payout,
+                multiplier,
+                ways: 1,
+            });
+        }
+        wins.extend(best);
+    }
+
+    wins
+}
+
+/// ライン上のシンボルが配当表の1行を満たすか調べ、満たす場合はワイルドによる倍率を返す
+///
+/// # 戻り値
+/// 成立すればSome(ワイルドの倍率の積)、成立しなければNone
+fn line_multiplier(line_symbols: &[SymbolId], entry: &PayEntry, symbols: &[SymbolDef], paytable: &Paytable) -> Option<u64> {
+    // 数える範囲（左からなら先頭の count 個、どこでもならライン全体）
+    let span = match entry.rule {
+        PayRule::Left => line_symbols.get(..entry.count)?,
+        PayRule::Anywhere => line_symbols,
+    };
+    // 対象（またはワイルド）のマス。パーシートで全組み合わせを回すので、集めずに数える
+    let matched = || span.iter().copied().filter(|&symbol| paytable.covers(entry, symbol) || symbols[symbol].wild);
+    let count = matched().count();
+    match entry.rule {
+        PayRule::Left if count < span.len() => return None,
+        PayRule::Anywhere if count < entry.count => return None,
+        _ => {}
+    }
+    // ワイルド自身の行はワイルドだけの並びで、先頭のワイルドがその行のシンボルの場合のみ成立する
+    if symbols[entry.symbol].wild {
+        return (matched().all(|symbol| symbols[symbol].wild) && matched().next() == Some(entry.symbol)).then_some(1);
+    }
+    // ワイルドだけの並びは通常のシンボルの役にならない
+    if matched().all(|symbol| symbols[symbol].wild) {
+        return None;
+    }
+    // 代わりになったワイルドの倍率を掛け合わせる（×2と×3なら×6）
+    Some(
+        matched()
+            .filter(|&symbol| symbols[symbol].wild)
+            .map(|symbol| symbols[symbol].wild_multiplier)
+            .product(),
+    )
+}
+
+/// 盤面に対してウェイ判定を行う
+/// 左のリールから配当表の数だけ隣り合うリールに対象のシンボル（またはワイルド）があれば、段を問わず成立する
+/// 各リールで対象になる段の数を掛け合わせたものがウェイ数で、配当はウェイ数倍になる
+/// 対象（シンボル・グループ）ごとに成立した行のうち最も配当の高いもの（同じなら配当表で先の行）だけを払う
+/// ワイルドだけのウェイは通常のシンボルの役にならない（ワイルド自身の役は先頭のリールがそのワイルドのウェイ）
+/// ワイルドの倍率はウェイごとに掛けて配当に含める（LineWin::multiplier は1）
+///
+/// # 引数
+/// * `reel_symbols` - リールごとの表示シンボル（[リール][段]）
+/// * `symbols` - シンボルの定義（ワイルドの判定に使う）
+/// * `paytable` - 配当倍率を引く配当表
+/// * `coins_per_line` - 1ウェイあたりの掛け枚数
+///
+/// # 戻り値
+/// 配当のある当選の一覧（ライン番号はNone）
+pub fn evaluate_ways(
+    reel_symbols: &[Vec<SymbolId>],
+    symbols: &[SymbolDef],
+    paytable: &Paytable,
+    coins_per_line: u64,
+) -> Vec<LineWin> {
+    let mut wins: Vec<LineWin> = Vec::new();
+
+    for (entry_index, entry) in paytable.entries().iter().enumerate() {
+        // スキャッタはウェイでは揃わない（配当は scatter::evaluate_scatters で盤面全体から数える）
+        if symbols[entry.symbol].scatter {
+            continue;
+        }
+        let Some((ways, weighted)) = count_ways(reel_symbols, entry, symbols, paytable) else {
+            continue;
         };
-        
-        // 現在位置から連続する3つのシンボルを配列で返す
-        // % REEL_SIZE で配列の境界を超えた場合に先頭に戻る（循環）
-        [
-            symbols[position],                           // 上段：現在位置
-            symbols[(position + 1) % REEL_SIZE],        // 中段：次の位置
-            symbols[(position + 2) % REEL_SIZE],        // 下段：その次の位置
-        ]
-    }
-
-    /// リールの回転処理メインループ（非同期関数）
-    /// tokio::spawn()によって別タスクで実行される
-    /// 回転中は一定間隔でリール位置を更新し続ける
-    pub async fn spin_loop(&self) {
-        let step_duration = Duration::from_millis(35); // 回転速度：35ミリ秒間隔（1周0.74秒）
-        
-        loop {  // 無限ループ（停止条件で抜ける）
-            // === 回転状態の確認 ===
-            // スコープブロック内でMutexロックを取得・即座に解放
-            let is_spinning = *self.is_spinning.lock().unwrap();
-            if !is_spinning {
-                break;  // 回転していなければループ終了
+        let win = LineWin {
+            line: None,
+            entry: entry_index,
+            symbol: entry.symbol,
+            count: entry.count,
+            payout: entry.multiplier * weighted * coins_per_line,
+            multiplier: 1,
+            ways,
+        };
+        // 同じ対象の行がすでに成立していれば、配当の高い方だけを残す
+        let same_target = wins.iter_mut().find(|other| {
+            let other = &paytable.entries()[other.entry];
+            other.symbol == entry.symbol && other.group == entry.group
+        });
+        match same_target {
+            Some(other) if other.payout >= win.payout => {}
+            Some(other) => *other = win,
+            None => wins.push(win),
+        }
+    }
+
+    wins
+}
+
+/// 配当表の1行が成立するウェイ数を数える
+///
+/// # 戻り値
+/// 成立すればSome((ウェイ数, ワイルドの倍率を掛けたウェイ数の合計))、成立しなければNone
+fn count_ways(
+    reel_symbols: &[Vec<SymbolId>],
+    entry: &PayEntry,
+    symbols: &[SymbolDef],
+    paytable: &Paytable,
+) -> Option<(u64, u64)> {
+    let reels = reel_symbols.get(..entry.count)?;
+    let wild_entry = symbols[entry.symbol].wild;
+    // すべてのウェイと、そのうちワイルドだけのウェイ（それぞれ倍率を掛けた合計も）
+    let (mut ways, mut weighted, mut wild_ways, mut wild_weighted) = (1u64, 1u64, 1u64, 1u64);
+    for (reel, rows) in reels.iter().enumerate() {
+        let (mut count, mut weight, mut wild_count, mut wild_weight) = (0u64, 0u64, 0u64, 0u64);
+        for &symbol in rows {
+            if symbols[symbol].wild {
+                // ワイルド自身の役は先頭のリールがそのワイルドの場合のみ
+                if wild_entry && reel == 0 && symbol != entry.symbol {
+                    continue;
+                }
+                let multiplier = if wild_entry { 1 } else { symbols[symbol].wild_multiplier };
+                wild_count += 1;
+                wild_weight += multiplier;
+                count += 1;
+                weight += multiplier;
+            } else if paytable.covers(entry, symbol) {
+                count += 1;
+                weight += 1;
             }
+        }
+        ways *= count;
+        weighted *= weight;
+        wild_ways *= wild_count;
+        wild_weighted *= wild_weight;
+    }
+    if !wild_entry {
+        ways -= wild_ways;
+        weighted -= wild_weighted;
+    }
+    (ways > 0).then_some((ways, weighted))
+}
+
+/// 停止制御（すべり）の設定
+/// 停止ボタンが押された位置から最大すべりコマ数までの範囲で、抽選結果に合う停止位置を選ぶ
+/// どの順にすべりコマ数を試すかは抽選結果ごとの制御テーブルで決める
+pub struct SlipControl {
+    max_slip: usize,                      // 最大すべりコマ数（パチスロは4コマ）
+    tables: Vec<(Outcome, Vec<usize>)>,   // 抽選結果ごとの制御テーブル（試すすべりコマ数の優先順）
+}
+
+impl SlipControl {
+    /// 最大すべりコマ数と制御テーブルから作成
+    /// テーブルのない抽選結果は、すべりの少ない順（0, 1, …, 最大）に試す
+    pub fn new(max_slip: usize, tables: Vec<(Outcome, Vec<usize>)>) -> Self {
+        Self { max_slip, tables }
+    }
+
+    /// 最大すべりコマ数
+    pub fn max_slip(&self) -> usize {
+        self.max_slip
+    }
+
+    /// 抽選結果に対応する、試すすべりコマ数の優先順
+    fn order(&self, outcome: Outcome) -> Vec<usize> {
+        self.tables
+            .iter()
+            .find(|(table_outcome, _)| *table_outcome == outcome)
+            .map(|(_, order)| order.clone())
+            .unwrap_or_else(|| (0..=self.max_slip).collect())
+    }
 
-            // === 停止要求の確認 ===
-            if *self.stop_requested.lock().unwrap() {
-                // 停止要求があった場合、回転状態をfalseに変更
-                let mut is_spinning = self.is_spinning.lock().unwrap();
-                *is_spinning = false;
-                break;  // ループ終了
+    /// 停止ボタンが押されたリールの停止位置を決める
+    /// 制御テーブルの順にすべりコマ数を試し、抽選結果を成立させられる最初の位置に止める
+    /// 範囲内で成立させられなければ取りこぼしとしてハズレになる位置を選び、
+    /// それも無理ならテーブルの先頭の位置に止める
+    ///
+    /// # 引数
+    /// * `machine` - マシン定義
+    /// * `paylines` - 有効ライン
+    /// * `outcome` - 抽選結果
+    /// * `stops` - 各リールの停止位置（停止済みのリールのみSome）
+    /// * `reel` - 停止させるリール
+    /// * `pressed` - ボタンが押された瞬間の位置
+    ///
+    /// # 戻り値
+    /// 停止位置
+    pub fn choose_stop(
+        &self,
+        machine: &MachineDef,
+        paylines: &[Vec<usize>],
+        outcome: Outcome,
+        stops: &[Option<usize>],
+        reel: usize,
+        pressed: usize,
+    ) -> usize {
+        let len = machine.reels[reel].len();
+        let candidates: Vec<usize> = self
+            .order(outcome)
+            .into_iter()
+            .map(|slip| (pressed + slip) % len)
+            .collect();
+        let mut fixed = stops.to_vec();
+        for target in [outcome, Outcome::Miss] {
+            for &candidate in &candidates {
+                fixed[reel] = Some(candidate);
+                if lottery::reachable(machine, paylines, target, &fixed) {
+                    return candidate;
+                }
             }
+        }
+        candidates[0]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::SessionRng;
+    use std::path::Path;
+    use rand::Rng;
 
-            // === リール位置の更新 ===
-            {
-                // スコープブロックでMutexロックの取得期間を制限
-                let mut position = self.position.lock().unwrap();
-                // 位置を1つ進める（21に達したら0に戻る循環処理）
-                *position = (*position + 1) % REEL_SIZE;
-            } // ここでMutexロックが解放される
-            
-            // === 待機処理 ===
-            // 非同期待機：他のタスクに実行権を譲りながら100ms待機
-            sleep(step_duration).await;
-        } // ループ終了時にリール停止完了
-    }
-}
-
-// 有効ライン（5ライン）の定義
-pub const PAYLINES: [[usize; 3]; 5] = [
-    [0, 0, 0], // 上段横一列
-    [1, 1, 1], // 中段横一列
-    [2, 2, 2], // 下段横一列
-    [0, 1, 2], // 斜め下がり
-    [2, 1, 0], // 斜め上がり
-];
-
-pub fn check_winnings(reels: &[Reel; 3]) -> Vec<usize> {
-    let mut winning_lines = Vec::new();
-    
-    let reel_symbols: Vec<[&str; DISPLAY_SIZE]> = reels
-        .iter()
-        .map(|reel| reel.get_visible_symbols())
-        .collect();
-
-    for (line_index, line) in PAYLINES.iter().enumerate() {
-        let symbols: Vec<&str> = line
+    /// 組み込みマシンのリールを指定位置から作る
+    fn reels(machine: &MachineDef, positions: &[usize]) -> Vec<Reel> {
+        positions
             .iter()
             .enumerate()
-            .map(|(reel_index, &row)| reel_symbols[reel_index][row])
-            .collect();
+            .map(|(id, &position)| Reel::new(id, machine, position))
+            .collect()
+    }
+
+    /// ワイルドの判定を調べるための5リール×1段・1ラインのマシン
+    /// ⭐（×2）と🌟（×3）がワイルド、💯がスキャッタ
+    fn wild_machine() -> MachineDef {
+        let source = r#"
+            name = "ワイルドのテスト"
+            rows = 1
+            line_options = [1]
+            reels = [["⭐", "🌟", "👑", "🍒", "💯"], ["👑"], ["👑"], ["👑"], ["👑"]]
+            paylines = [[0, 0, 0, 0, 0]]
+
+            [timing]
+            spin_step_ms = 35
+
+            [[symbols]]
+            glyph = "⭐"
+            name = "スター"
+            wild = true
+            wild_multiplier = 2
+
+            [[symbols]]
+            glyph = "🌟"
+            name = "きらきら"
+            wild = true
+            wild_multiplier = 3
+
+            [[symbols]]
+            glyph = "👑"
+            name = "クラウン"
+
+            [[symbols]]
+            glyph = "🍒"
+            name = "チェリー"
+
+            [[symbols]]
+            glyph = "💯"
+            name = "スキャッタ"
+            scatter = true
 
-        // 3つのシンボルが同じかチェック
-        if symbols[0] == symbols[1] && symbols[1] == symbols[2] {
-            winning_lines.push(line_index);
+            [[paytable]]
+            symbol = "⭐"
+            count = 5
+            multiplier = 500
+
+            [[paytable]]
+            symbol = "👑"
+            count = 5
+            multiplier = 100
+
+            [[paytable]]
+            symbol = "👑"
+            count = 3
+            multiplier = 20
+
+            [[paytable]]
+            symbol = "💯"
+            count = 3
+            multiplier = 2
+        "#;
+        MachineDef::from_toml(source, Path::new("wild.toml")).unwrap()
+    }
+
+    /// 空白区切りの絵文字をシンボルの並びにする
+    fn symbols_of(machine: &MachineDef, glyphs: &str) -> Vec<SymbolId> {
+        glyphs
+            .split_whitespace()
+            .map(|glyph| machine.symbols.iter().position(|symbol| symbol.glyph == glyph).unwrap())
+            .collect()
+    }
+
+    /// 1段の盤面（リールごとに1シンボル）
+    fn one_row_window(machine: &MachineDef, glyphs: &str) -> Vec<Vec<SymbolId>> {
+        symbols_of(machine, glyphs).into_iter().map(|symbol| vec![symbol]).collect()
+    }
+
+    #[test]
+    fn line_multiplier_substitutes_and_multiplies_wilds() {
+        let machine = wild_machine();
+        // (ライン, 配当表の行, 期待するワイルドの倍率)
+        let cases = [
+            ("👑 👑 👑 👑 👑", 1, Some(1)),
+            ("👑 ⭐ 👑 👑 👑", 1, Some(2)),  // ワイルドが1つ代わりになる
+            ("⭐ 👑 🌟 👑 👑", 1, Some(6)),  // ×2と×3を掛け合わせる
+            ("⭐ ⭐ 👑 👑 👑", 1, Some(4)),  // 同じワイルドが2つなら×4
+            ("⭐ ⭐ 👑 🍒 🍒", 2, Some(4)),  // 3つの役では先頭3つの中のワイルドを掛ける
+            ("👑 👑 👑 ⭐ 🍒", 2, Some(1)),  // 数える範囲の外のワイルドは倍率に入らない
+            ("⭐ ⭐ ⭐ ⭐ ⭐", 1, None),     // ワイルドだけの並びは通常のシンボルの役にならない
+            ("⭐ ⭐ ⭐ ⭐ ⭐", 0, Some(1)),  // ワイルド自身の役は倍率を乗せない
+            ("⭐ 🌟 ⭐ ⭐ ⭐", 0, Some(1)),  // 先頭がその行のワイルドなら別のワイルドも数える
+            ("🌟 ⭐ ⭐ ⭐ ⭐", 0, None),     // 先頭が別のワイルドならその行は成立しない
+            ("👑 ⭐ 💯 👑 👑", 2, None),     // スキャッタはワイルドでも埋まらない
+        ];
+        for (line, entry, expected) in cases {
+            let entry_def = &machine.paytable.entries()[entry];
+            let actual = line_multiplier(&symbols_of(&machine, line), entry_def, &machine.symbols, &machine.paytable);
+            assert_eq!(actual, expected, "{} を行{}で判定", line, entry);
         }
     }
 
-    winning_lines
-}
\ No newline at end of file
+    #[test]
+    fn wilds_never_stand_in_for_scatters() {
+        let machine = wild_machine();
+        let window = one_row_window(&machine, "💯 ⭐ 💯 💯 🌟");
+        let wins = evaluate(&machine, &window, machine.paylines.active(1), 1);
+        assert!(wins.is_empty(), "ライン上でスキャッタが揃った: {:?}", wins);
+        let scatter = symbols_of(&machine, "💯")[0];
+        assert_eq!(crate::scatter::count_in_window(&window, scatter), 3);
+    }
+
+    #[test]
+    fn paytable_rules_pick_the_best_row_for_a_line() {
+        // 左から2つ・左に1つのチェリー、グループ（🍒・🍉）、ライン上どこでものベルを持つマシン
+        let machine = MachineDef::from_toml(
+            include_str!("../machines/classic-3x1.toml"),
+            Path::new("machines/classic-3x1.toml"),
+        )
+        .unwrap();
+        // (ライン, 期待する当選（配当表の行の表示と配当）)
+        let cases = [
+            ("🍒 🍒 🍒", Some(("🍒 🍒 🍒", 5))),                  // 3つ揃いがグループより優先
+            ("🍒 🍒 🔔", Some(("🍒×2（左から）", 2))),
+            ("🍒 ⬜ 🍒", Some(("🍒×1（左から）", 1))),            // 左から続かない2つ目は数えない
+            ("🍒 🔔 🔔", Some(("🍒×1（左から）", 1))),            // 同じ配当なら配当表で先の行
+            ("⬜ 🍒 🍒", None),                                   // 1番目にチェリーがなければ当たらない
+            ("🍒 🍉 🍒", Some(("［フルーツ］×3", 2))),            // グループの中で混ざっていてもよい
+            ("🍉 🍒 🍉", Some(("［フルーツ］×3", 2))),
+            ("🍉 🍉 🍉", Some(("🍉 🍉 🍉", 20))),                 // 単独の3つ揃いがグループより優先
+            ("🔔 👑 🔔", Some(("🔔×2（ライン上どこでも）", 1))),
+            ("👑 🔔 🔔", Some(("🔔×2（ライン上どこでも）", 1))),
+            ("👑 👑 🔔", None),
+        ];
+        for (line, expected) in cases {
+            let window = one_row_window(&machine, line);
+            let wins = evaluate(&machine, &window, machine.paylines.active(1), 1);
+            assert!(wins.len() <= 1, "1ラインで複数の行を払った: {:?}", wins);
+            let actual = wins.first().map(|win| (machine.pay_label(&machine.paytable.entries()[win.entry]), win.payout));
+            assert_eq!(actual, expected.map(|(label, payout)| (label.to_string(), payout)), "{} の判定", line);
+        }
+    }
+
+    /// リールごとの段を空白区切りの絵文字で並べた盤面
+    fn window_of(machine: &MachineDef, reels: &[&str]) -> Vec<Vec<SymbolId>> {
+        reels.iter().map(|rows| symbols_of(machine, rows)).collect()
+    }
+
+    #[test]
+    fn ways_multiply_the_matching_rows_of_each_reel() {
+        let machine = MachineDef::from_toml(
+            include_str!("../machines/ways-3x3.toml"),
+            Path::new("machines/ways-3x3.toml"),
+        )
+        .unwrap();
+        // 💯は左リールに2段・中リールに2段・右リールに1段で 2 × 2 × 1 = 4ウェイ
+        let window = window_of(&machine, &["💯 🎩 💯", "💯 💯 ⚪", "🎩 💯 🍒"]);
+        let wins = evaluate_ways(&window, &machine.symbols, &machine.paytable, 2);
+        assert_eq!(wins.len(), 1, "{:?}", wins);
+        // 💯×2（3倍）も同じ4ウェイで成立するが、配当の高い💯×3（30倍）だけを払う
+        assert_eq!(machine.pay_label(&machine.paytable.entries()[wins[0].entry]), "💯 💯 💯");
+        assert_eq!(wins[0].ways, 4);
+        assert_eq!(wins[0].payout, 30 * 4 * 2);
+
+        // 右リールに💯がなければ左の2リールだけの役になる
+        let window = window_of(&machine, &["💯 🎩 💯", "💯 💯 ⚪", "🎩 ⚪ 🍒"]);
+        let wins = evaluate_ways(&window, &machine.symbols, &machine.paytable, 1);
+        assert_eq!(wins.len(), 1, "{:?}", wins);
+        assert_eq!(machine.pay_label(&machine.paytable.entries()[wins[0].entry]), "💯×2（左から）");
+        assert_eq!((wins[0].ways, wins[0].payout), (4, 3 * 4));
+    }
+
+    #[test]
+    fn count_ways_weights_each_way_by_its_wilds() {
+        let machine = wild_machine();
+        let crown_3 = &machine.paytable.entries()[2];
+        // 段ごとの重みは👑が1、⭐が2、🌟が3：(1 + 2) × 1 × (3 + 1) = 12
+        let window = window_of(&machine, &["👑 ⭐", "👑 🍒", "🌟 👑"]);
+        assert_eq!(count_ways(&window, crown_3, &machine.symbols, &machine.paytable), Some((4, 12)));
+        // ワイルドだけのウェイ（⭐ ⭐ ⭐、重み8）は除く：4 - 1 = 3ウェイ、18 - 8 = 10
+        let window = window_of(&machine, &["⭐ 👑", "⭐ 🍒", "⭐ 👑"]);
+        assert_eq!(count_ways(&window, crown_3, &machine.symbols, &machine.paytable), Some((3, 10)));
+        // 途中のリールに対象がなければ成立しない
+        let window = window_of(&machine, &["👑 ⭐", "🍒 💯", "👑 👑"]);
+        assert_eq!(count_ways(&window, crown_3, &machine.symbols, &machine.paytable), None);
+    }
+
+    /// 1つのフレームの中で矛盾がないことを確かめる
+    /// 同じ時刻に回し始めたリールは、同じ時刻で読めば全リールが同じコマ数だけ進んでいる
+    fn assert_consistent(machine: &MachineDef, frame: &Frame, origins: &[usize], started_ms: u64) {
+        let steps = (frame.at_ms - started_ms) / machine.spin_step.as_millis() as u64;
+        for (reel, (view, &origin)) in frame.reels.iter().zip(origins).enumerate() {
+            let len = machine.reels[reel].len();
+            if view.spinning {
+                assert!(!view.sliding, "回転中のリールがすべり中になっている: {:?}", view);
+                assert_eq!(view.display, (origin + steps as usize) % len, "リール{}が別の時刻の位置になっている", reel);
+            } else if !view.sliding {
+                assert_eq!(view.display, view.position, "止まったリールの表示が停止位置と違う: {:?}", view);
+            }
+        }
+    }
+
+    #[test]
+    fn frame_reads_every_reel_at_the_same_instant() {
+        let machine = MachineDef::builtin();
+        let origins = [3, 10, 17];
+        let mut reels = reels(&machine, &origins);
+        for reel in &mut reels {
+            reel.start_spinning(1_000);
+        }
+        // 1コマ35ミリ秒・21コマで1周0.74秒
+        let frame = Frame::capture(&reels, 1_000 + 35 * 21);
+        for (reel, view) in frame.reels.iter().enumerate() {
+            assert_eq!(view.display, (origins[reel] + 21) % machine.reels[reel].len());
+        }
+        assert_consistent(&machine, &frame, &origins, 1_000);
+    }
+
+    #[test]
+    fn stopped_reel_slides_to_its_stop_and_settles() {
+        let machine = MachineDef::builtin();
+        let mut reel = Reel::new(0, &machine, 0);
+        reel.start_spinning(0);
+        let pressed = reel.position_at(350);
+        let stop = (pressed + 3) % machine.reels[0].len();
+        reel.stop_at(stop, 350);
+        let sliding = reel.frame(351);
+        assert!(!sliding.spinning && sliding.sliding);
+        assert_eq!(sliding.display, pressed);
+        // 3コマのすべりは 1.5 + 2 + 2.5 コマ分の時間で終わる
+        let settled = reel.frame(350 + 35 * 6);
+        assert!(!settled.sliding);
+        assert_eq!(settled.display, stop);
+        assert_eq!(reel.get_visible_symbols(), reel.symbols_at(settled.display));
+    }
+
+    /// 操作を乱数でつなぎ続け、どの時刻のフレームも途中の状態を混ぜていないことを確かめる
+    #[test]
+    fn stress_random_operations_never_tear_a_frame() {
+        let machine = MachineDef::builtin();
+        let mut rng = SessionRng::seeded(20_240_601);
+        let mut reels = reels(&machine, &[0, 0, 0]);
+        let mut now_ms = 0;
+        let mut origins = vec![0; reels.len()];
+        let mut started_ms = 0;
+        for _ in 0..200_000 {
+            now_ms += rng.gen_range(0..60);
+            match rng.gen_range(0..4) {
+                0 if !reels.iter().any(|reel| reel.is_spinning()) => {
+                    origins = reels.iter().map(|reel| reel.position()).collect();
+                    started_ms = now_ms;
+                    for reel in &mut reels {
+                        reel.start_spinning(now_ms);
+                    }
+                }
+                1 => {
+                    let index = rng.gen_range(0..reels.len());
+                    let slip = rng.gen_range(0..=4);
+                    let pressed = reels[index].position_at(now_ms);
+                    reels[index].stop_at(pressed + slip, now_ms);
+                }
+                2 => {
+                    let index = rng.gen_range(0..reels.len());
+                    reels[index].nudge(rng.gen());
+                }
+                _ => {}
+            }
+            let frame = Frame::capture(&reels, now_ms);
+            // 同じ時刻で読み直せば必ず同じフレームになる
+            assert_eq!(frame, Frame::capture(&reels, now_ms));
+            assert_consistent(&machine, &frame, &origins, started_ms);
+        }
+    }
+
+    #[test]
+    fn lines_above_the_active_count_are_never_paid() {
+        let machine = MachineDef::builtin();
+        let all = machine.paylines.all();
+        let (reels, len) = (machine.reels.len(), machine.reels[0].len());
+        let mut excluded = 0;
+        // 組み込みマシンのすべての停止位置の組み合わせで、有効ライン数ごとに判定し直す
+        for stops in 0..len.pow(reels as u32) {
+            let window: Vec<Vec<SymbolId>> = (0..reels)
+                .map(|reel| visible_symbols(&machine.reels[reel], stops / len.pow(reel as u32) % len, machine.rows))
+                .collect();
+            let every_line = evaluate(&machine, &window, all, 1);
+            for &lines in &machine.line_options {
+                let active = machine.paylines.active(lines);
+                assert_eq!(active, &all[..lines]);
+                // 有効ラインの当選は、全ラインの判定のうち先頭 lines 本の当選と同じ
+                let paid = evaluate(&machine, &window, active, 1);
+                let expected: Vec<LineWin> = every_line.iter().filter(|win| win.line < Some(lines)).cloned().collect();
+                assert_eq!(paid, expected);
+                excluded += every_line.len() - paid.len();
+            }
+        }
+        // 有効でないラインに揃った組み合わせを実際に含んでいる
+        assert!(excluded > 0);
+    }
+
+    /// すべりを調べるための3リール×1段・1ラインのマシン（どのリールも 🔔⬜⬜⬜🍉⬜⬜⬜ の8コマ）
+    fn slip_machine() -> MachineDef {
+        let source = r#"
+            name = "すべりのテスト"
+            rows = 1
+            line_options = [1]
+            reels = [
+                ["🔔", "⬜", "⬜", "⬜", "🍉", "⬜", "⬜", "⬜"],
+                ["🔔", "⬜", "⬜", "⬜", "🍉", "⬜", "⬜", "⬜"],
+                ["🔔", "⬜", "⬜", "⬜", "🍉", "⬜", "⬜", "⬜"],
+            ]
+            paylines = [[0, 0, 0]]
+
+            [timing]
+            spin_step_ms = 35
+
+            [[symbols]]
+            glyph = "🔔"
+            name = "ベル"
+
+            [[symbols]]
+            glyph = "🍉"
+            name = "スイカ"
+
+            [[symbols]]
+            glyph = "⬜"
+            name = "ブランク"
+
+            [[paytable]]
+            symbol = "🔔"
+            count = 3
+            multiplier = 10
+
+            [[paytable]]
+            symbol = "🍉"
+            count = 3
+            multiplier = 20
+        "#;
+        MachineDef::from_toml(source, Path::new("slip.toml")).unwrap()
+    }
+
+    #[test]
+    fn stops_slide_into_the_drawn_outcome_within_max_slip() {
+        let machine = slip_machine();
+        let slip = SlipControl::new(2, Vec::new());
+        let bell = Outcome::Win { symbol: 0, count: 3 };
+        let paylines = machine.paylines.active(1);
+        // (押した位置, 期待する停止位置)
+        let cases = [
+            (0, 0),  // 押した位置で揃う
+            (6, 0),  // 2コマすべって🔔を引き込む
+            (7, 0),
+            (5, 5),  // 🔔まで3コマは届かず、取りこぼしてハズレ
+            (1, 1),  // すべりは進む向きだけなので、通り過ぎた🔔には戻らない
+        ];
+        for (pressed, expected) in cases {
+            let stop = slip.choose_stop(&machine, paylines, bell, &[None, None, None], 0, pressed);
+            assert_eq!(stop, expected, "押した位置 {}", pressed);
+        }
+        // どこで押しても max_slip コマより多くはすべらない
+        for outcome in [bell, Outcome::Win { symbol: 1, count: 3 }, Outcome::Miss] {
+            for pressed in 0..8 {
+                let stop = slip.choose_stop(&machine, paylines, outcome, &[Some(0), None, None], 1, pressed);
+                assert!((stop + 8 - pressed) % 8 <= slip.max_slip(), "{:?} 押した位置 {}", outcome, pressed);
+            }
+        }
+    }
+
+    #[test]
+    fn stops_slide_past_a_win_that_was_not_drawn() {
+        let machine = slip_machine();
+        let slip = SlipControl::new(2, Vec::new());
+        let paylines = machine.paylines.active(1);
+        // 🍉🍉と止まった右リールを🍉で押しても、ハズレの抽選なら1コマすべらせて外す
+        let stops = [Some(4), Some(4), None];
+        assert_eq!(slip.choose_stop(&machine, paylines, Outcome::Miss, &stops, 2, 4), 5);
+        // 🔔の抽選でも🔔は届かないので、🍉ではなくハズレの位置に止める
+        let bell = Outcome::Win { symbol: 0, count: 3 };
+        assert_eq!(slip.choose_stop(&machine, paylines, bell, &stops, 2, 4), 5);
+        // 引いた役なら押した位置のまま揃える
+        let melon = Outcome::Win { symbol: 1, count: 3 };
+        assert_eq!(slip.choose_stop(&machine, paylines, melon, &stops, 2, 4), 4);
+    }
+}
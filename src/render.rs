@@ -0,0 +1,768 @@
+// === 画面描画モジュール ===
+// ゲームの状態のスナップショット（Snapshot）から画面を組み立てる
+// 画面の配置（どの行に何を出すか）はここで決め、実際の出力先はバックエンド（Renderer の実装）に任せる
+// ターミナル版は crossterm のバックエンド、テストでは文字の格子に書き込むバックエンド（TextGrid）を使う
+use std::io;
+
+use crate::bonus::{BonusKind, Transition};
+use crate::game::Snapshot;
+use crate::gamble::Gamble;
+use crate::lottery::Outcome;
+use crate::reel::{Evaluation, LineWin};
+use crate::scatter::FreeSpinEvent;
+use crate::setting::{self, SettingStats, SETTINGS};
+
+// リール枠の表示開始行（0行目はタイトル、1行目はクレジット情報）
+const REELS_TOP: u16 = 2;
+
+/// 描画する画面
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum View {
+    Initial,  // 初期画面（タイトル・リール・操作説明）
+    Reels,    // リールと結果（初期画面のうち変化する部分だけを描き直す）
+    Help,     // 配当表
+}
+
+/// 文字色（実際の色はバックエンドが決める）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Color {
+    White,    // 通常
+    Red,      // BIG・お知らせ・ダブルアップ失敗
+    Yellow,   // 当選・終了の表示
+    Green,    // ホールド・ナッジの案内
+    Cyan,     // REG
+    Magenta,  // フリースピン
+}
+
+/// 消去する範囲
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Clear {
+    All,             // 画面全体
+    CurrentLine,     // カーソルのある行
+    FromCursorDown,  // カーソルの位置から画面の最後まで
+}
+
+/// 画面の出力先
+/// バックエンドは4つの基本操作だけを実装し、画面の組み立ては render に任せる
+pub trait Renderer {
+    /// カーソルを移動する（x: 桁、y: 行、どちらも0始まり）
+    fn move_to(&mut self, x: u16, y: u16) -> io::Result<()>;
+
+    /// これから書く文字の色を変える
+    fn set_color(&mut self, color: Color) -> io::Result<()>;
+
+    /// カーソルの位置に文字を書き、カーソルを書いた分だけ右へ進める
+    fn print(&mut self, text: &str) -> io::Result<()>;
+
+    /// 画面の一部を消す（カーソルは動かさない）
+    fn clear(&mut self, clear: Clear) -> io::Result<()>;
+
+    /// ゲームの状態から画面を描く
+    ///
+    /// # 引数
+    /// * `view` - 描画する画面
+    /// * `game` - ゲームの状態のスナップショット
+    fn render(&mut self, view: View, game: &Snapshot) -> io::Result<()> {
+        Screen { out: self, game }.draw(view)
+    }
+}
+
+/// 1回の描画
+/// スナップショットを読み、出力先に画面を書き込む
+struct Screen<'o, 'g, R: ?Sized> {
+    out: &'o mut R,            // 出力先
+    game: &'o Snapshot<'g>,    // 描画するゲームの状態
+}
+
+impl<R: Renderer + ?Sized> Screen<'_, '_, R> {
+    /// 画面全体を描く
+    fn draw(&mut self, view: View) -> io::Result<()> {
+        match view {
+            View::Initial => self.display_initial_screen(),
+            View::Reels => self.display_reels(),
+            View::Help => self.display_help_screen(),
+        }
+    }
+
+    /// リールの表示を行う関数
+    /// スロットマシンの見た目を描画
+    fn display_reels(&mut self) -> io::Result<()> {
+        // クレジット情報（HUD）をタイトル直下に表示
+        self.display_credits()?;
+
+        // カーソルを指定位置に移動してから表示
+        self.out.move_to(0, 2)?;
+
+        // 各リールから現在表示すべきシンボルを取得
+        // カスケードの演出中は連鎖の盤面を表示する（当選して消えるマスは💥）
+        let reel_symbols: Vec<Vec<&str>> = match self.game.displayed_cascade_step {
+            Some((index, exploding)) => {
+                let step = &self.game.cascade[index];
+                step.window
+                    .iter()
+                    .zip(&step.removed)
+                    .map(|(column, removed)| {
+                        column
+                            .iter()
+                            .zip(removed)
+                            .map(|(&symbol, &removed)| if exploding && removed { "💥" } else { self.game.machine.glyph(symbol) })
+                            .collect()
+                    })
+                    .collect()
+            }
+            None => self
+                .game
+                .reels
+                .iter()
+                .zip(&self.game.frame.reels)
+                .map(|(reel, frame)| {
+                    reel.symbols_at(frame.display)
+                        .into_iter()
+                        .map(|symbol| self.game.machine.glyph(symbol))
+                        .collect()
+                })
+                .collect(),
+        };
+
+        // 罫線をリール数に合わせて組み立てる
+        let border = |left: &str, middle: &str, right: &str| {
+            format!("{}{}{}", left, vec!["────"; self.game.reels.len()].join(middle), right)
+        };
+
+        // 各行を個別に出力して正確な表示を確保
+        self.out.move_to(0, REELS_TOP)?;
+        self.out.print(&border("┌", "┬", "┐"))?;
+        for row in 0..self.game.machine.rows {
+            let y = REELS_TOP + 1 + 2 * row as u16;
+            let cells: Vec<&str> = reel_symbols.iter().map(|symbols| symbols[row]).collect();
+            self.out.move_to(0, y)?;
+            self.out.print(&format!("│ {} │", cells.join(" │ ")))?;
+            self.out.move_to(0, y + 1)?;
+            if row + 1 < self.game.machine.rows {
+                self.out.print(&border("├", "┼", "┤"))?;
+            } else {
+                self.out.print(&border("└", "┴", "┘"))?;
+            }
+        }
+
+        // ボーナスの消化状況（リール枠の下）
+        self.display_bonus()?;
+
+        // リールの状態表示（各リールが回転中か停止中かを表示）
+        let status_top = self.status_top();
+        let sliding = self.game.frame.any_sliding();
+        for (i, (reel, frame)) in self.game.reels.iter().zip(&self.game.frame.reels).enumerate() {
+            let status = if frame.spinning {
+                format!("リール{}: 回転中", reel.reel_id + 1)
+            } else {
+                // ホールドの権利は結果と同じく、すべり終わってから見せる
+                let hold = match self.game.holds {
+                    Some(holds) if holds[i] => "  🔒ホールド",
+                    Some(_) if !sliding => "  （ホールドできます）",
+                    _ if self.game.held.get(i) == Some(&true) => "  🔒ホールド",
+                    _ => "",
+                };
+                format!("リール{}: 停止{}", reel.reel_id + 1, hold)
+            };
+            self.out.move_to(0, status_top + i as u16)?;
+            self.out.clear(Clear::CurrentLine)?;
+            self.out.print(&status)?;
+        }
+
+        // 結果表示エリアをクリア
+        let result_top = self.result_top();
+        self.out.move_to(0, result_top)?;
+        self.out.clear(Clear::FromCursorDown)?;
+
+        // お知らせ（クレジット不足など）があれば優先して表示
+        if let Some(message) = self.game.message {
+            self.out.move_to(0, result_top)?;
+            self.out.set_color(Color::Red)?;
+            self.out.print(message)?;
+            self.out.set_color(Color::White)?;
+            return Ok(());
+        }
+
+        // 当選結果の表示（精算済みのゲームがある場合のみ）
+        // 精算は停止ボタンの時点で済んでいるが、結果はリールがすべり終わってから見せる
+        if self.game.frame.any_sliding() {
+            return Ok(());
+        }
+        if let Some(wins) = self.game.last_result {
+            if !wins.is_empty() || !self.game.last_scatters.is_empty() {
+                // カスケードの演出中は表示中の段階までの当選と、その時点の獲得合計を見せる
+                let steps: Vec<&[LineWin]> = match self.game.displayed_cascade_step {
+                    Some((index, _)) => self.game.cascade[..=index].iter().map(|step| step.wins.as_slice()).collect(),
+                    None => vec![wins],
+                };
+                let shown: Vec<LineWin> = steps.iter().flat_map(|wins| wins.iter().cloned()).collect();
+                let total = shown.iter().map(|win| win.payout).sum::<u64>()
+                    + self.game.last_scatters.iter().map(|win| win.payout).sum::<u64>();
+                // 当選時の表示
+                self.out.move_to(0, result_top)?;
+                self.out.set_color(Color::Yellow)?;
+                self.out.print(&format!("🎉 当選! {}クレジット獲得 🎉", total))?;
+                if self.game.last_jackpot > 0 {
+                    self.out.print(&format!("  💰 ジャックポット {}クレジット！", self.game.last_jackpot))?;
+                }
+                if self.game.jackpot_taken {
+                    self.out.print("  ジャックポットは別のセッションが先に受け取りました")?;
+                }
+                // スキャッタの当選（ラインに関係なく盤面全体で数える）
+                if !self.game.last_scatters.is_empty() {
+                    self.out.move_to(0, result_top + 2)?;
+                    self.out.print("スキャッタ: ")?;
+                    for win in self.game.last_scatters {
+                        self.out.print(&format!("{}×{} +{} ", self.game.machine.glyph(win.symbol), win.count, win.payout))?;
+                    }
+                }
+                self.out.move_to(0, result_top + 1)?;
+                let ways = self.game.machine.evaluation == Evaluation::Ways;
+                self.out.print(if ways { "当選ウェイ: " } else { "当選ライン: " })?;
+                for (index, wins) in steps.iter().enumerate() {
+                    // 連鎖で成立した当選は何回目の連鎖か（と連鎖の倍率）を添える
+                    if index > 0 && !wins.is_empty() {
+                        self.out.print(&format!("→ 連鎖{}（×{}） ", index, self.game.cascade[index].multiplier))?;
+                    }
+                    for win in wins.iter() {
+                        let label = self.game.machine.pay_label(&self.game.machine.paytable.entries()[win.entry]);
+                        // ワイルドの倍率が乗ったラインは倍率も表示
+                        let multiplier = if win.multiplier > 1 { format!(" ワイルド×{}", win.multiplier) } else { String::new() };
+                        let text = match win.line {
+                            Some(line) => format!("{}({}{} +{}) ", line + 1, label, multiplier, win.payout),
+                            // ウェイ判定は成立したウェイ数を表示（配当はウェイ数倍）
+                            None => format!("{} {}ウェイ +{}  ", label, win.ways, win.payout),
+                        };
+                        self.out.print(&text)?;
+                    }
+                }
+                self.out.set_color(Color::White)?;
+                
+                // 当選ラインの詳細表示
+                self.out.move_to(0, result_top + 3)?;
+                self.display_paylines(&shown)?;
+
+                // ダブルアップ（当選ラインの一覧の下）
+                if let Some(gamble) = self.game.gamble {
+                    self.display_gamble(gamble)?;
+                }
+            } else {
+                // ハズレ時の表示
+                self.out.move_to(0, result_top)?;
+                self.out.print("残念、ハズレです")?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// フリースピンの消化状況の表示（ボーナスと同じ行）
+    /// フリースピン中は残り回数と獲得合計、終了直後はその結果を表示する
+    fn display_free_spins(&mut self) -> io::Result<()> {
+        let text = match (self.game.free_spins, self.game.free_spin_event) {
+            (Some(spins), event) => {
+                let notice = match event {
+                    Some(FreeSpinEvent::Awarded(count)) => format!("  {}回獲得！", count),
+                    Some(FreeSpinEvent::Retriggered(count)) => format!("  {}回追加！", count),
+                    _ => String::new(),
+                };
+                format!(
+                    "★ フリースピン  残り{}回（{}回消化）  獲得 {}{}",
+                    spins.remaining, spins.played, spins.win, notice
+                )
+            }
+            (None, Some(FreeSpinEvent::Ended { played, win })) => {
+                format!("フリースピン終了（{}回・獲得 {}）", played, win)
+            }
+            _ => return Ok(()),
+        };
+        let color = if self.game.free_spins.is_some() { Color::Magenta } else { Color::Yellow };
+        self.out.set_color(color)?;
+        self.out.print(&text)?;
+        self.out.set_color(Color::White)?;
+        Ok(())
+    }
+
+    /// ダブルアップの状況と直前のカードの表示（当選ラインの一覧の下）
+    fn display_gamble(&mut self, gamble: &Gamble) -> io::Result<()> {
+        let lines = match self.game.machine.evaluation {
+            Evaluation::Lines => self.game.machine.paylines.all().len(),
+            Evaluation::Ways => 0,
+        };
+        let top = self.result_top() + 4 + lines as u16;
+        let ladder = self.game.machine.gamble.as_ref().map_or(0, |def| def.ladder);
+        let status = if gamble.open {
+            format!(
+                "🎲 ダブルアップ: {}クレジット（あと{}回まで）  Z: 赤 / X: 黒 / A: ハイ(8〜K) / S: ロー(A〜6) / C: 受け取る",
+                gamble.stake,
+                ladder - gamble.round
+            )
+        } else if gamble.stake > 0 {
+            format!("🎲 ダブルアップ終了: {}クレジット獲得", gamble.stake)
+        } else {
+            "🎲 ダブルアップ失敗: 配当は没収されました".to_string()
+        };
+        self.out.move_to(0, top)?;
+        self.out.set_color(if gamble.stake > 0 { Color::Yellow } else { Color::Red })?;
+        self.out.print(&status)?;
+        if let Some(draw) = gamble.last {
+            let verdict = if draw.result > 0 { "的中！" } else { "ハズレ…" };
+            self.out.move_to(0, top + 1)?;
+            self.out.print(&format!(
+                "カード {}  予想 {} → {}  {} → {}",
+                draw.card,
+                draw.guess.label(),
+                verdict,
+                draw.stake,
+                draw.result
+            ))?;
+        }
+        self.out.set_color(Color::White)?;
+        Ok(())
+    }
+
+    /// ホールド・ナッジの権利の表示（ボーナスと同じ行）
+    fn display_holds_and_nudges(&mut self) -> io::Result<()> {
+        let mut notices = Vec::new();
+        if self.game.holds.is_some() {
+            notices.push(format!("ホールド: 1〜{}キーで選んでスペース", self.game.reels.len()));
+        }
+        if self.game.nudges > 0 {
+            notices.push(format!("ナッジ残り{}回: {}キーで下へ（Shiftで上へ）", self.game.nudges, nudge_keys(self.game.reels.len())));
+        }
+        if notices.is_empty() {
+            return Ok(());
+        }
+        self.out.set_color(Color::Green)?;
+        self.out.print(&format!("★ {}", notices.join("  ")))?;
+        self.out.set_color(Color::White)?;
+        Ok(())
+    }
+
+    /// リール状態表示の開始行（リール枠の2行下）
+    fn status_top(&self) -> u16 {
+        REELS_TOP + 2 * self.game.machine.rows as u16 + 2
+    }
+
+    /// 結果表示エリアの開始行（リール状態表示の1行下）
+    fn result_top(&self) -> u16 {
+        self.status_top() + self.game.reels.len() as u16 + 1
+    }
+
+    /// クレジット情報の表示
+    /// 所持クレジット・ベット額・直前の獲得額をタイトル直下に表示する
+    /// ボーナス中は状態ごとの色で表示する（BIG: 赤、REG: 水色、フリースピン: 紫）
+    fn display_credits(&mut self) -> io::Result<()> {
+        let free_spins = self.game.displayed_free_spins;
+        let color = if free_spins {
+            Color::Magenta
+        } else {
+            self.game.displayed_bonus.map_or(Color::White, bonus_color)
+        };
+        // フリースピン中はベットなし（掛け枚数・ライン数は始まったときのまま）
+        let bet = if free_spins { "フリー".to_string() } else { self.game.wallet.bet().to_string() };
+        // ウェイ判定のマシンはライン数の代わりにウェイ数を表示
+        let lines = match self.game.machine.evaluation {
+            Evaluation::Lines => format!("{}ライン", self.game.wallet.lines()),
+            Evaluation::Ways => format!("{}ウェイ", self.game.machine.total_ways()),
+        };
+        self.out.move_to(0, 1)?;
+        self.out.clear(Clear::CurrentLine)?;
+        self.out.set_color(color)?;
+        // ジャックポットのあるマシンは積立額も表示（他のセッションの積立も反映される）
+        let jackpot = self.game.jackpot_amount.map_or(String::new(), |amount| format!("  JP: {}", amount));
+        self.out.print(&format!(
+            "クレジット: {}  ベット: {} ({}枚×{})  獲得: {}{}",
+            self.game.wallet.balance(),
+            bet,
+            self.game.wallet.coins_per_line(),
+            lines,
+            self.game.wallet.last_win(),
+            jackpot
+        ))?;
+        self.out.set_color(Color::White)?;
+        Ok(())
+    }
+
+    /// ボーナスの消化状況の表示（リール枠とリール状態表示の間の行）
+    /// ボーナス中は消化ゲーム数と払い出し（1枚掛け換算）、終了直後はその結果を表示する
+    fn display_bonus(&mut self) -> io::Result<()> {
+        // すべり終わるまでは前の表示のままにする（結果を先に見せない）
+        if self.game.frame.any_sliding() {
+            return Ok(());
+        }
+        self.out.move_to(0, self.status_top() - 1)?;
+        self.out.clear(Clear::CurrentLine)?;
+        if self.game.machine.free_spins.is_some() {
+            return self.display_free_spins();
+        }
+        if self.game.machine.hold.is_some() || self.game.machine.nudge.is_some() {
+            return self.display_holds_and_nudges();
+        }
+        let text = match (self.game.state.run(), self.game.transition) {
+            (Some(run), transition) => {
+                let bonus = &self.game.machine.bonuses[run.bonus];
+                let cap = bonus.payout_cap.map_or(String::new(), |cap| format!("/{}", cap));
+                let started = if matches!(transition, Some(Transition::Started(_))) { "  突入！" } else { "" };
+                format!(
+                    "★ {}  {}/{}ゲーム  獲得 {}{}枚{}",
+                    bonus.kind, run.games, bonus.games, run.paid, cap, started
+                )
+            }
+            (None, Some(Transition::Ended { kind, games, paid })) => {
+                format!("{} 終了（{}ゲーム・獲得 {}枚）", kind, games, paid)
+            }
+            _ => return Ok(()),
+        };
+        // 終了の表示は当選表示と同じ黄色
+        let color = self.game.state.kind().map_or(Color::Yellow, bonus_color);
+        self.out.set_color(color)?;
+        self.out.print(&text)?;
+        self.out.set_color(Color::White)?;
+        Ok(())
+    }
+
+    /// 初期画面を表示する関数
+    /// ゲーム開始時にタイトル、リール、操作説明を表示
+    fn display_initial_screen(&mut self) -> io::Result<()> {
+        // 画面をクリアして初期表示
+        self.out.clear(Clear::All)?;
+        self.out.move_to(0, 0)?;
+
+        // タイトル
+        self.out.print("🎰 スロットマシン 🎰")?;
+
+        // リール表示
+        self.display_reels()?;
+
+        // 操作説明を下部に表示（結果表示エリアの下）
+        let last_reel = self.game.reels.len();
+        let line_options: Vec<String> = self.game.machine.line_options.iter().map(|n| n.to_string()).collect();
+        let mut controls = vec![
+            "操作方法:".to_string(),
+            "スペースキー: 全リール回転開始".to_string(),
+            format!("1〜{}キー: 指定したリールを停止", last_reel),
+            "Enterキー: 回転中で一番左のリールを停止".to_string(),
+            format!("←キー: 左端リール停止 / ↓キー: 中央リール停止（{}番目） / →キー: 右端リール停止", last_reel / 2 + 1),
+            "Bキー: ベット枚数切替".to_string(),
+            match self.game.machine.evaluation {
+                Evaluation::Lines => format!("Lキー: 有効ライン数切替（{}）", line_options.join("/")),
+                Evaluation::Ways => format!("Lキー: 有効ライン数切替（{}ウェイのマシンでは使えません）", self.game.machine.total_ways()),
+            },
+            "Hキー: 配当表の表示/非表示".to_string(),
+            "ESCキー: ゲーム終了".to_string(),
+        ];
+        // ホールド・ナッジのあるマシンはその操作も案内する
+        if self.game.machine.hold.is_some() {
+            controls.insert(controls.len() - 2, format!("1〜{}キー（停止中）: ホールドの切替", last_reel));
+        }
+        if self.game.machine.gamble.is_some() {
+            controls.insert(
+                controls.len() - 2,
+                "Z/X/A/Sキー: ダブルアップ（赤/黒/ハイ/ロー） / Cキー: 受け取る".to_string(),
+            );
+        }
+        if self.game.machine.nudge.is_some() {
+            controls.insert(
+                controls.len() - 2,
+                format!("{}キー: ナッジ（1コマ下へ、Shiftで上へ）", nudge_keys(last_reel)),
+            );
+        }
+        let controls_top = self.result_top() + 6;
+        for (i, text) in controls.iter().enumerate() {
+            self.out.move_to(0, controls_top + i as u16)?;
+            self.out.print(text)?;
+        }
+
+        Ok(())
+    }
+
+    /// ヘルプ画面（配当表）を表示する関数
+    /// 各シンボルの配当倍率と現在の掛け枚数での払い出し額を一覧表示
+    fn display_help_screen(&mut self) -> io::Result<()> {
+        self.out.clear(Clear::All)?;
+        self.out.move_to(0, 0)?;
+        self.out.print(&format!("📖 配当表（{}） 📖", self.game.machine.name))?;
+        self.out.move_to(0, 2)?;
+        self.out.print(&format!(
+            "1{}あたり{}枚掛けの払い出し",
+            if self.game.machine.evaluation == Evaluation::Ways { "ウェイ" } else { "ライン" },
+            self.game.wallet.coins_per_line()
+        ))?;
+
+        for (i, entry) in self.game.machine.paytable.entries().iter().enumerate() {
+            self.out.move_to(0, (4 + i) as u16)?;
+            let symbol = &self.game.machine.symbols[entry.symbol];
+            // スキャッタはどこに出ても総ベットの倍数を払い出す
+            if symbol.scatter {
+                self.out.print(&format!(
+                    "{}  どこでも{}個 総ベットx{:<4} → {}クレジット  （{}）",
+                    symbol.glyph,
+                    entry.count,
+                    entry.multiplier,
+                    entry.multiplier * self.game.wallet.bet(),
+                    symbol.name
+                ))?;
+                continue;
+            }
+            self.out.print(&format!(
+                "{}  x{:<4} → {}クレジット  （{}）",
+                self.game.machine.pay_label(entry),
+                entry.multiplier,
+                entry.multiplier * self.game.wallet.coins_per_line(),
+                self.game.machine.pay_name(entry)
+            ))?;
+        }
+
+        let mut bottom = (5 + self.game.machine.paytable.entries().len()) as u16;
+        // ワイルドの説明
+        for symbol in self.game.machine.symbols.iter().filter(|symbol| symbol.wild) {
+            let multiplier = if symbol.wild_multiplier > 1 {
+                format!("、揃った配当が{}倍", symbol.wild_multiplier)
+            } else {
+                String::new()
+            };
+            self.out.move_to(0, bottom)?;
+            self.out.print(&format!("{} はワイルド（他のシンボルの代わりになる{}）", symbol.glyph, multiplier))?;
+            bottom += 1;
+        }
+        // フリースピンの説明
+        if let Some(free_spins) = &self.game.machine.free_spins {
+            self.out.move_to(0, bottom)?;
+            self.out.print(&format!(
+                "{} がどこでも{}個以上でフリースピン{}回（フリースピン中に出ると{}回追加）",
+                self.game.machine.glyph(free_spins.symbol),
+                free_spins.count,
+                free_spins.spins,
+                free_spins.spins
+            ))?;
+            bottom += 1;
+        }
+        // ダブルアップの説明
+        if let Some(gamble) = &self.game.machine.gamble {
+            self.out.move_to(0, bottom)?;
+            self.out.print(&format!(
+                "当たりのあとダブルアップ: 赤/黒・ハイ/ローを当てると倍（最大{}回、外れると没収）",
+                gamble.ladder
+            ))?;
+            bottom += 1;
+        }
+        // ジャックポットの説明
+        if let Some(jackpot) = &self.game.machine.jackpot {
+            self.out.move_to(0, bottom)?;
+            self.out.print(&format!(
+                "{} でジャックポット: ベットの{}%を積み立て、当たると積立額をすべて獲得（初期値 {}）",
+                self.game.machine.pay_label(&self.game.machine.paytable.entries()[jackpot.entry]),
+                jackpot.contribution,
+                jackpot.seed
+            ))?;
+            bottom += 1;
+        }
+        if self.game.machine.symbols.iter().any(|symbol| symbol.wild)
+            || self.game.machine.free_spins.is_some()
+            || self.game.machine.gamble.is_some()
+            || self.game.machine.jackpot.is_some()
+        {
+            bottom += 1;
+        }
+        if let Some(stats) = self.game.stats {
+            bottom = self.display_setting_stats(stats, bottom)?;
+        }
+        self.out.move_to(0, bottom)?;
+        self.out.print("Hキー: ゲームに戻る")?;
+        Ok(())
+    }
+
+    /// 設定推測の表をヘルプ画面に表示する
+    /// 当選役ごとに、このセッションの実測の出現間隔と、設定1〜6での理論上の出現間隔を並べる
+    ///
+    /// # 戻り値
+    /// 表の次の行
+    fn display_setting_stats(&mut self, stats: &SettingStats, top: u16) -> io::Result<u16> {
+        let ratio = self.game.wallet.lines() as f64 / self.game.machine.paylines.all().len() as f64;
+        let header: Vec<String> = (1..=SETTINGS).map(|setting| pad_left(&format!("設定{}", setting), 9)).collect();
+        self.out.move_to(0, top)?;
+        self.out.print(&format!("設定推測（{}ゲーム・{}ライン有効時の出現間隔）", stats.games, self.game.wallet.lines()))?;
+        self.out.move_to(0, top + 1)?;
+        self.out.print(&format!("{}{}  役", pad_left("実測", 9), header.join("")))?;
+        let mut y = top + 2;
+        let Some(table) = self.game.machine.lottery(1) else {
+            return Ok(y + 1);
+        };
+        for (index, entry) in table.entries().iter().enumerate() {
+            if entry.outcome == Outcome::Miss {
+                continue;
+            }
+            let expected: Vec<String> = (1..=SETTINGS)
+                .map(|setting| {
+                    let lottery = self.game.machine.lottery(setting).unwrap_or(table);
+                    let probability = lottery.probability(&lottery.entries()[index]) * ratio;
+                    let cycle = if probability > 0.0 { format!("1/{:.1}", 1.0 / probability) } else { "-".to_string() };
+                    pad_left(&cycle, 9)
+                })
+                .collect();
+            self.out.move_to(0, y)?;
+            self.out.print(&format!(
+                "{}{}  {}",
+                pad_left(&setting::frequency(stats.games, stats.counts[index]), 9),
+                expected.join(""),
+                self.game.machine.outcome_label(entry.outcome)
+            ))?;
+            y += 1;
+        }
+        Ok(y + 1)
+    }
+
+    /// 有効ラインの表示
+    /// 当選ライン情報を画面に表示する
+    /// ウェイ判定のマシンはラインの代わりに判定方式を表示する
+    fn display_paylines(&mut self, wins: &[LineWin]) -> io::Result<()> {
+        if self.game.machine.evaluation == Evaluation::Ways {
+            self.out.print(&format!(
+                "全{}ウェイ: 左のリールから隣り合うリールに続けて揃えば段は問わない",
+                self.game.machine.total_ways()
+            ))?;
+            return Ok(());
+        }
+        self.out.print("有効ライン:")?;
+        // 全てのペイラインを表示し、当選したラインをマークする（無効ラインは「--」）
+        for (i, line) in self.game.machine.paylines.all().iter().enumerate() {
+            let status = if wins.iter().any(|win| win.line == Some(i)) {
+                "🎯"
+            } else if i >= self.game.wallet.lines() {
+                "--"
+            } else {
+                "  "
+            };
+            let rows: Vec<String> = line.iter().map(|row| (row + 1).to_string()).collect();
+            self.out.move_to(0, self.result_top() + 4 + i as u16)?;
+            self.out.print(&format!("{} ライン{}: [{}]", status, i + 1, rows.join(", ")))?;
+        }
+        Ok(())
+    }
+}
+
+/// ボーナスの種類ごとの表示色（BIG: 赤、REG: 水色）
+fn bonus_color(kind: BonusKind) -> Color {
+    match kind {
+        BonusKind::Big => Color::Red,
+        BonusKind::Reg => Color::Cyan,
+    }
+}
+
+// ナッジキー（数字キーの1段下の列、左から1番目のリール）
+// 画面の案内に使うため、キー入力の割り当てもこの並びに合わせる
+pub const NUDGE_KEYS: [char; 9] = ['q', 'w', 'e', 'r', 't', 'y', 'u', 'i', 'o'];
+
+/// 画面の案内に使うナッジキーの範囲（「Q〜E」など）
+fn nudge_keys(reels: usize) -> String {
+    format!("{}〜{}", NUDGE_KEYS[0].to_ascii_uppercase(), NUDGE_KEYS[reels - 1].to_ascii_uppercase())
+}
+
+/// 表示幅に合わせて左側を空白で埋める（全角文字・絵文字は2桁として数える）
+fn pad_left(text: &str, width: usize) -> String {
+    let used: usize = text.chars().map(|c| if c.is_ascii() { 1 } else { 2 }).sum();
+    format!("{}{}", " ".repeat(width.saturating_sub(used)), text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::text_grid::TextGrid;
+    use crate::{MachineDef, ReelCommand, SessionRng, SlotMachine};
+
+    /// 組み込みマシンで回し始め、指定した時刻にリールを左から止める
+    fn play(seed: u64, stops: &[u64]) -> SlotMachine {
+        let mut slot_machine = SlotMachine::new(MachineDef::builtin(), SessionRng::seeded(seed), None);
+        slot_machine.apply(ReelCommand::StartAll, 0);
+        for (index, &at_ms) in stops.iter().enumerate() {
+            slot_machine.apply(ReelCommand::Stop(index), at_ms);
+        }
+        slot_machine
+    }
+
+    /// 指定した時刻のリールと結果の画面（タイトルの行は描かないので1行目は空）
+    fn screen(slot_machine: &mut SlotMachine, now_ms: u64) -> String {
+        slot_machine.tick(now_ms);
+        let mut grid = TextGrid::new();
+        grid.render(View::Reels, &slot_machine.snapshot()).unwrap();
+        grid.text()
+    }
+
+    #[test]
+    fn spinning_screen() {
+        let mut slot_machine = play(1, &[]);
+        assert_eq!(
+            screen(&mut slot_machine, 350),
+            r"
+クレジット: 93  ベット: 7 (1枚×7ライン)  獲得: 0
+┌────┬────┬────┐
+│ 🍀 │ 💯 │ ⚪ │
+├────┼────┼────┤
+│ 🏀 │ 🍀 │ ⭐ │
+├────┼────┼────┤
+│ 🍀 │ 🍒 │ 🏀 │
+└────┴────┴────┘
+
+リール1: 回転中
+リール2: 回転中
+リール3: 回転中"
+        );
+    }
+
+    #[test]
+    fn losing_screen() {
+        let mut slot_machine = play(1, &[350, 700, 1050]);
+        assert_eq!(
+            screen(&mut slot_machine, 1400),
+            r"
+クレジット: 93  ベット: 7 (1枚×7ライン)  獲得: 0
+┌────┬────┬────┐
+│ 🍀 │ 🍒 │ 🍀 │
+├────┼────┼────┤
+│ 🏀 │ 🏀 │ ⚪ │
+├────┼────┼────┤
+│ 🍀 │ ⭐ │ ⭐ │
+└────┴────┴────┘
+
+リール1: 停止
+リール2: 停止
+リール3: 停止
+
+残念、ハズレです"
+        );
+    }
+
+    #[test]
+    fn winning_screen() {
+        let mut slot_machine = play(9, &[350, 700, 1050]);
+        assert_eq!(
+            screen(&mut slot_machine, 1400),
+            r"
+クレジット: 108  ベット: 7 (1枚×7ライン)  獲得: 15
+┌────┬────┬────┐
+│ ⚪ │ 🍒 │ 🏀 │
+├────┼────┼────┤
+│ 🍀 │ 🏀 │ 🍀 │
+├────┼────┼────┤
+│ 🏀 │ 🎩 │ ⚪ │
+└────┴────┴────┘
+
+リール1: 停止
+リール2: 停止
+リール3: 停止
+
+🎉 当選! 15クレジット獲得 🎉
+当選ライン: 5(🏀 🏀 🏀 +15)
+
+有効ライン:
+   ライン1: [2, 2, 2]
+   ライン2: [1, 1, 1]
+   ライン3: [3, 3, 3]
+   ライン4: [1, 2, 3]
+🎯 ライン5: [3, 2, 1]
+   ライン6: [1, 2, 1]
+   ライン7: [3, 2, 3]"
+        );
+    }
+}
@@ -0,0 +1,57 @@
+// === 乱数生成モジュール ===
+// スロットマシンが使う乱数をまとめて管理する
+// シードを記録しておけば、同じシードから同じ乱数列を再現できる（バグ報告の再現用）
+use rand::{RngCore, SeedableRng};
+use rand_chacha::ChaCha8Rng;          // シードから決まった乱数列を生成する（バージョン間で再現性あり）
+
+/// セッション全体で共有する乱数生成器
+/// 中身の生成器は差し替え可能（テスト用の固定列などを入れられる）
+pub struct SessionRng {
+    seed: u64,                          // この乱数列を作ったシード（セッションログに記録する）
+    source: Box<dyn RngCore + Send>,    // 実際の乱数生成器
+}
+
+impl SessionRng {
+    /// シードから乱数生成器を作成
+    pub fn seeded(seed: u64) -> Self {
+        Self::with_source(seed, Box::new(ChaCha8Rng::seed_from_u64(seed)))
+    }
+
+    /// シード未指定時：OSの乱数でシードを決めて作成
+    pub fn from_entropy() -> Self {
+        Self::seeded(rand::random())
+    }
+
+    /// 任意の乱数生成器を差し込んで作成
+    ///
+    /// # 引数
+    /// * `seed` - ログに記録するシード（生成器の内容を説明する値）
+    /// * `source` - 実際に乱数を生成する生成器
+    pub fn with_source(seed: u64, source: Box<dyn RngCore + Send>) -> Self {
+        Self { seed, source }
+    }
+
+    /// この乱数列のシード
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+}
+
+// rand::Rng の各種メソッド（gen_range など）をそのまま使えるようにする
+impl RngCore for SessionRng {
+    fn next_u32(&mut self) -> u32 {
+        self.source.next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.source.next_u64()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.source.fill_bytes(dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.source.try_fill_bytes(dest)
+    }
+}
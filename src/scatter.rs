@@ -0,0 +1,242 @@
+// === スキャッタ・フリースピンモジュール ===
+// スキャッタはペイラインに関係なく、盤面のどこにいくつ出たかで総ベットの倍数を払い出す
+// フリースピンの対象シンボルが決まった数以上出ると、ベット不要のフリースピンが始まる
+// フリースピン中に再び出た場合は回数が追加される（リトリガー）
+use crate::machine::{MachineDef, SymbolId};
+
+/// スキャッタ1種類分の当選情報
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScatterWin {
+    pub entry: usize,      // 成立した配当表の行（Paytable::entries の添字）
+    pub symbol: SymbolId,  // スキャッタのシンボル
+    pub count: usize,      // 盤面に出た数
+    pub payout: u64,       // 配当クレジット（倍率 × 総ベット）
+}
+
+/// フリースピンの定義（どのシンボルがいくつ出たら何回か）
+pub struct FreeSpinsDef {
+    pub symbol: SymbolId,  // 対象のスキャッタ
+    pub count: usize,      // 始まるのに必要な数（盤面のどこでもよい）
+    pub spins: u32,        // 与えられる回数（リトリガー時も同じ回数を追加）
+}
+
+/// 消化中のフリースピン
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FreeSpins {
+    pub remaining: u32,  // 残り回数
+    pub played: u32,     // 消化した回数
+    pub win: u64,        // フリースピン中の獲得合計（終了時にまとめて残高に加算）
+}
+
+/// 1ゲームの精算で起きたフリースピンの出来事
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FreeSpinEvent {
+    /// フリースピンが始まった（与えられた回数）
+    Awarded(u32),
+    /// フリースピン中に回数が追加された（追加された回数）
+    Retriggered(u32),
+    /// フリースピンが終わった（消化した回数と獲得合計）
+    Ended { played: u32, win: u64 },
+}
+
+/// 盤面に出たシンボルの数を数える
+pub fn count_in_window(window: &[Vec<SymbolId>], symbol: SymbolId) -> usize {
+    window.iter().flatten().filter(|&&visible| visible == symbol).count()
+}
+
+/// 盤面のスキャッタの配当を判定する
+/// 出た数以下で最も多い数の配当表の行を使う（3個・4個・5個の配当があれば、6個は5個の配当）
+///
+/// # 引数
+/// * `window` - リールごとの表示シンボル（[リール][段]）
+/// * `machine` - マシン定義（スキャッタのシンボルと配当表）
+/// * `total_bet` - 総ベット（掛け枚数 × ライン数）
+///
+/// # 戻り値
+/// 配当のあるスキャッタの一覧
+pub fn evaluate_scatters(window: &[Vec<SymbolId>], machine: &MachineDef, total_bet: u64) -> Vec<ScatterWin> {
+    let mut wins = Vec::new();
+    for (symbol, def) in machine.symbols.iter().enumerate() {
+        if !def.scatter {
+            continue;
+        }
+        let count = count_in_window(window, symbol);
+        let pay = machine
+            .paytable
+            .entries()
+            .iter()
+            .enumerate()
+            .filter(|(_, entry)| entry.symbol == symbol && entry.count <= count)
+            .max_by_key(|(_, entry)| entry.count);
+        if let Some((index, entry)) = pay {
+            wins.push(ScatterWin {
+                entry: index,
+                symbol,
+                count,
+                payout: entry.multiplier * total_bet,
+            });
+        }
+    }
+    wins
+}
+
+/// 1ゲームの盤面でフリースピンを進める
+/// フリースピン中なら1回消化し、対象のシンボルが決まった数以上出ていれば回数を追加する
+/// 通常時に出ていればフリースピンを始める
+///
+/// # 引数
+/// * `round` - 消化中のフリースピン（通常時はNone）
+/// * `def` - フリースピンの定義
+/// * `window` - そのゲームの盤面
+/// * `payout` - そのゲームの配当（フリースピン中なら獲得合計に加える）
+///
+/// # 戻り値
+/// フリースピンの開始・追加・終了があればその内容
+pub fn advance(
+    round: &mut Option<FreeSpins>,
+    def: &FreeSpinsDef,
+    window: &[Vec<SymbolId>],
+    payout: u64,
+) -> Option<FreeSpinEvent> {
+    let triggered = count_in_window(window, def.symbol) >= def.count;
+    match round {
+        None if triggered => {
+            *round = Some(FreeSpins {
+                remaining: def.spins,
+                played: 0,
+                win: 0,
+            });
+            Some(FreeSpinEvent::Awarded(def.spins))
+        }
+        None => None,
+        Some(spins) => {
+            spins.remaining -= 1;
+            spins.played += 1;
+            spins.win += payout;
+            if triggered {
+                spins.remaining += def.spins;
+                return Some(FreeSpinEvent::Retriggered(def.spins));
+            }
+            if spins.remaining > 0 {
+                return None;
+            }
+            let ended = FreeSpinEvent::Ended {
+                played: spins.played,
+                win: spins.win,
+            };
+            *round = None;
+            Some(ended)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    /// スキャッタを調べるための3リール×3段のマシン
+    /// 💯がスキャッタ（3個で総ベットの2倍、4個で10倍、3個以上でフリースピン5回）
+    fn scatter_machine() -> MachineDef {
+        let source = r#"
+            name = "スキャッタのテスト"
+            rows = 3
+            line_options = [1]
+            reels = [["💯", "👑", "🍒"], ["💯", "👑", "🍒"], ["💯", "👑", "🍒"]]
+            paylines = [[1, 1, 1]]
+
+            [timing]
+            spin_step_ms = 35
+
+            [free_spins]
+            symbol = "💯"
+            count = 3
+            spins = 5
+
+            [[symbols]]
+            glyph = "💯"
+            name = "スキャッタ"
+            scatter = true
+
+            [[symbols]]
+            glyph = "👑"
+            name = "クラウン"
+
+            [[symbols]]
+            glyph = "🍒"
+            name = "チェリー"
+
+            [[paytable]]
+            symbol = "👑"
+            count = 3
+            multiplier = 20
+
+            [[paytable]]
+            symbol = "💯"
+            count = 3
+            multiplier = 2
+
+            [[paytable]]
+            symbol = "💯"
+            count = 4
+            multiplier = 10
+        "#;
+        MachineDef::from_toml(source, Path::new("scatter.toml")).unwrap()
+    }
+
+    /// 上段から順に、空白区切りの絵文字で書いた盤面を [リール][段] にする
+    fn window_of(machine: &MachineDef, rows: [&str; 3]) -> Vec<Vec<SymbolId>> {
+        let id = |glyph: &str| machine.symbols.iter().position(|symbol| symbol.glyph == glyph).unwrap();
+        let rows: Vec<Vec<SymbolId>> = rows.iter().map(|row| row.split_whitespace().map(id).collect()).collect();
+        (0..rows[0].len()).map(|reel| rows.iter().map(|row| row[reel]).collect()).collect()
+    }
+
+    #[test]
+    fn scatters_are_counted_across_the_whole_window() {
+        let machine = scatter_machine();
+        // (盤面, 期待する (出た数, 配当)（総ベット5）)
+        let cases = [
+            (["💯 🍒 👑", "👑 🍒 🍒", "🍒 👑 💯"], None),           // 2個では配当なし
+            (["💯 🍒 👑", "👑 💯 🍒", "🍒 👑 💯"], Some((3, 10))),  // ラインに関係なく斜めでも
+            (["💯 🍒 👑", "💯 🍒 👑", "💯 🍒 👑"], Some((3, 10))),  // 同じリールにいくつあってもよい
+            (["💯 🍒 💯", "👑 💯 🍒", "🍒 👑 💯"], Some((4, 50))),
+            (["💯 💯 💯", "👑 🍒 🍒", "💯 👑 💯"], Some((5, 50))),  // 配当のない数は、それ以下で最も多い数の配当
+        ];
+        for (rows, expected) in cases {
+            let wins = evaluate_scatters(&window_of(&machine, rows), &machine, 5);
+            let paid = wins.iter().map(|win| (win.count, win.payout)).next();
+            assert_eq!(paid, expected, "{:?}", rows);
+            assert!(wins.iter().all(|win| machine.paytable.entries()[win.entry].symbol == win.symbol));
+        }
+    }
+
+    #[test]
+    fn free_spins_are_awarded_retriggered_and_ended() {
+        let machine = scatter_machine();
+        let def = machine.free_spins.as_ref().unwrap();
+        let two = window_of(&machine, ["💯 🍒 👑", "👑 🍒 🍒", "🍒 👑 💯"]);
+        let three = window_of(&machine, ["💯 🍒 👑", "👑 💯 🍒", "🍒 👑 💯"]);
+        let spins = |remaining, played, win| Some(FreeSpins { remaining, played, win });
+
+        // 通常時は3個以上で始まる（始まったゲームの配当は数えない）
+        let mut round = None;
+        assert_eq!(advance(&mut round, def, &two, 0), None);
+        assert_eq!(round, None);
+        assert_eq!(advance(&mut round, def, &three, 10), Some(FreeSpinEvent::Awarded(5)));
+        assert_eq!(round, spins(5, 0, 0));
+
+        // フリースピン中は1回ずつ消化して配当を貯め、3個以上で同じ回数を追加する
+        assert_eq!(advance(&mut round, def, &two, 10), None);
+        assert_eq!(round, spins(4, 1, 10));
+        assert_eq!(advance(&mut round, def, &three, 20), Some(FreeSpinEvent::Retriggered(5)));
+        assert_eq!(round, spins(8, 2, 30));
+        for _ in 0..7 {
+            assert_eq!(advance(&mut round, def, &two, 0), None);
+        }
+        assert_eq!(round, spins(1, 9, 30));
+
+        // 残りがなくなると、消化した回数と獲得合計を返して通常時に戻る
+        assert_eq!(advance(&mut round, def, &two, 5), Some(FreeSpinEvent::Ended { played: 10, win: 35 }));
+        assert_eq!(round, None);
+    }
+}
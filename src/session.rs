@@ -0,0 +1,317 @@
+// === セッションログモジュール ===
+// シード・操作（時刻付き）・精算結果をテキストで記録し、replay で読み戻す
+// リールの停止位置は「シード」と「操作の時刻」だけで決まるため、ログから同じゲームを再現できる
+//
+// 書式（1行1項目）:
+//   gh-slot-session 1
+//   seed <シード>
+//   machine <定義ファイルのパス | builtin>
+//   setting <設定>           … --setting で設定を指定したときのみ（隠した設定はシードから抽選し直す）
+//   @ <ミリ秒> <操作>        … 操作を受け付けた時刻（セッション開始から）と操作（最後の exit がセッションの終了）
+//   = <停止位置…> | <配当>   … 全リール停止後の精算結果（再生時の照合用）
+//   jackpot <クレジット>     … 直前の精算でジャックポットを受け取った額（積立額は他のセッションと共有のため再現できない）
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, LineWriter, Write};
+use std::path::{Path, PathBuf};
+
+use crate::command::ReelCommand;
+
+// ログの先頭行（書式のバージョン）
+const HEADER: &str = "gh-slot-session 1";
+
+// 組み込みマシンを表す machine 行の値
+const BUILTIN_MACHINE: &str = "builtin";
+
+/// 1ゲーム分の精算結果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpinResult {
+    pub stops: Vec<usize>,  // 各リールの停止位置（左から）
+    pub payout: u64,        // 獲得クレジット
+}
+
+// ログ用の表現（例: "3 17 8 | 20"）
+impl fmt::Display for SpinResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let stops: Vec<String> = self.stops.iter().map(|stop| stop.to_string()).collect();
+        write!(f, "{} | {}", stops.join(" "), self.payout)
+    }
+}
+
+/// セッションログの書き込み
+/// 1行ごとにファイルへ書き出す（異常終了してもそこまでの操作が残る）
+pub struct SessionLog {
+    out: LineWriter<File>,
+}
+
+impl SessionLog {
+    /// ログファイルを作成してヘッダーを書き込む
+    ///
+    /// # 引数
+    /// * `path` - ログファイルのパス（既存のファイルは上書き）
+    /// * `seed` - セッションの乱数シード
+    /// * `machine` - マシン定義ファイルのパス（組み込みマシンならNone）
+    /// * `setting` - --setting で指定した設定（指定がなければNone）
+    pub fn create(path: &Path, seed: u64, machine: Option<&Path>, setting: Option<usize>) -> io::Result<Self> {
+        let mut out = LineWriter::new(File::create(path)?);
+        writeln!(out, "{}", HEADER)?;
+        writeln!(out, "seed {}", seed)?;
+        match machine {
+            Some(machine) => writeln!(out, "machine {}", machine.display())?,
+            None => writeln!(out, "machine {}", BUILTIN_MACHINE)?,
+        }
+        if let Some(setting) = setting {
+            writeln!(out, "setting {}", setting)?;
+        }
+        Ok(Self { out })
+    }
+
+    /// 操作を記録する
+    pub fn command(&mut self, at_ms: u64, command: ReelCommand) -> io::Result<()> {
+        writeln!(self.out, "@ {} {}", at_ms, command)
+    }
+
+    /// 精算結果を記録する
+    pub fn result(&mut self, result: &SpinResult) -> io::Result<()> {
+        writeln!(self.out, "= {}", result)
+    }
+
+    /// 受け取ったジャックポットを記録する
+    pub fn jackpot(&mut self, amount: u64) -> io::Result<()> {
+        writeln!(self.out, "jackpot {}", amount)
+    }
+}
+
+/// ログの1項目
+pub enum Entry {
+    /// 操作（セッション開始からのミリ秒と操作）
+    Command { at_ms: u64, command: ReelCommand },
+    /// 精算結果
+    Result(SpinResult),
+    /// 受け取ったジャックポット（クレジット）
+    Jackpot(u64),
+}
+
+/// 読み込んだセッションログ
+pub struct Recording {
+    pub seed: u64,                 // 乱数シード
+    pub machine: Option<PathBuf>,  // マシン定義ファイル（組み込みならNone）
+    pub setting: Option<usize>,    // 指定された設定（隠して抽選したならNone）
+    pub entries: Vec<Entry>,       // 操作と精算結果（記録順）
+}
+
+impl Recording {
+    /// ログファイルを読み込む
+    ///
+    /// # 戻り値
+    /// 読み込んだログ、または利用者向けのエラーメッセージ
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = fs::read_to_string(path)
+            .map_err(|err| format!("セッションログを読み込めません: {}: {}", path.display(), err))?;
+        Self::parse(&text).map_err(|message| format!("セッションログの書式が不正です: {}: {}", path.display(), message))
+    }
+
+    /// ログの文字列を解釈する（エラーは「N行目: 理由」）
+    fn parse(text: &str) -> Result<Self, String> {
+        let mut lines = text.lines().enumerate().map(|(i, line)| (i + 1, line.trim()));
+        // 先頭の3行（ヘッダー・シード・マシン）は順番が決まっている
+        let mut header = |key: &str| -> Result<String, String> {
+            let (number, line) = lines
+                .next()
+                .ok_or_else(|| format!("「{}」の行がありません", key.trim()))?;
+            line.strip_prefix(key)
+                .map(|value| value.trim().to_string())
+                .ok_or_else(|| format!("{}行目: 「{}」の行が必要です", number, key.trim()))
+        };
+        if !header(HEADER)?.is_empty() {
+            return Err("1行目: 対応していないログ形式です".to_string());
+        }
+        let seed = header("seed ")?;
+        let seed = seed
+            .parse()
+            .map_err(|_| format!("2行目: シードが不正です: {}", seed))?;
+        let machine = match header("machine ")?.as_str() {
+            BUILTIN_MACHINE => None,
+            path => Some(PathBuf::from(path)),
+        };
+
+        let mut setting = None;
+        let mut entries = Vec::new();
+        for (number, line) in lines {
+            if line.is_empty() {
+                continue;
+            }
+            if let Some(rest) = line.strip_prefix("setting ") {
+                setting = Some(
+                    rest.trim()
+                        .parse()
+                        .map_err(|_| format!("{}行目: 設定が不正です: {}", number, rest))?,
+                );
+                continue;
+            }
+            let entry = if let Some(rest) = line.strip_prefix("@ ") {
+                let (at_ms, command) = rest
+                    .trim()
+                    .split_once(' ')
+                    .ok_or_else(|| format!("{}行目: 操作がありません", number))?;
+                Entry::Command {
+                    at_ms: at_ms
+                        .parse()
+                        .map_err(|_| format!("{}行目: 時刻が不正です: {}", number, at_ms))?,
+                    command: command
+                        .parse()
+                        .map_err(|message| format!("{}行目: {}", number, message))?,
+                }
+            } else if let Some(rest) = line.strip_prefix("= ") {
+                Entry::Result(
+                    parse_result(rest).ok_or_else(|| format!("{}行目: 精算結果が不正です: {}", number, rest))?,
+                )
+            } else if let Some(rest) = line.strip_prefix("jackpot ") {
+                Entry::Jackpot(
+                    rest.trim()
+                        .parse()
+                        .map_err(|_| format!("{}行目: ジャックポットの額が不正です: {}", number, rest))?,
+                )
+            } else {
+                return Err(format!("{}行目: 解釈できない行です: {}", number, line));
+            };
+            entries.push(entry);
+        }
+        Ok(Self {
+            seed,
+            machine,
+            setting,
+            entries,
+        })
+    }
+}
+
+impl Recording {
+    /// セッションが終わっているか（最後に exit の操作が記録されている）
+    pub fn finished(&self) -> bool {
+        self.entries
+            .iter()
+            .any(|entry| matches!(entry, Entry::Command { command: ReelCommand::Exit, .. }))
+    }
+
+    /// 再生の後で設定を明かしてよいか
+    /// 隠した設定はシードから抽選し直せるため、遊んでいる最中のログを再生すると設定推測の答えが分かってしまう。
+    /// 終了が記録されたログ（または --setting で指定した設定）のときだけ明かす
+    pub fn reveals_setting(&self) -> bool {
+        self.setting.is_some() || self.finished()
+    }
+}
+
+/// 精算結果の行（"3 17 8 | 20"）を解釈する
+fn parse_result(text: &str) -> Option<SpinResult> {
+    let (stops, payout) = text.split_once('|')?;
+    let stops = stops
+        .split_whitespace()
+        .map(|stop| stop.parse().ok())
+        .collect::<Option<Vec<usize>>>()?;
+    Some(SpinResult {
+        stops,
+        payout: payout.trim().parse().ok()?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::ManualClock;
+    use crate::game::{Applied, SlotMachine};
+    use crate::machine::MachineDef;
+    use crate::rng::SessionRng;
+    use crate::ticker::{Ticker, FRAME};
+    use rand::Rng;
+
+    /// 手で進める時計で遊んだセッションを記録し、記録した精算結果を返す
+    /// 操作の順番と間隔は player の乱数で決める
+    fn record(log: &Path, machine_path: Option<&Path>, setting: Option<usize>, seed: u64) -> Vec<SpinResult> {
+        let machine = machine_path.map_or_else(MachineDef::builtin, |path| MachineDef::load(path).unwrap());
+        let mut slot_machine = SlotMachine::new(machine, SessionRng::seeded(seed), setting);
+        let mut ticker = Ticker::new(ManualClock::new(), FRAME);
+        let mut log = SessionLog::create(log, seed, machine_path, setting).unwrap();
+        let mut player = SessionRng::seeded(seed + 1);
+        let mut results = Vec::new();
+        for _ in 0..300 {
+            let command = match player.gen_range(0..5) {
+                0 => ReelCommand::StartAll,
+                1 => ReelCommand::StopNext,
+                _ => ReelCommand::Stop(player.gen_range(0..3)),
+            };
+            // 押すまでの間隔（0〜20ティック）
+            for _ in 0..player.gen_range(0..=20) {
+                ticker.wait();
+            }
+            let at_ms = ticker.now_ms();
+            slot_machine.tick(at_ms);
+            log.command(at_ms, command).unwrap();
+            if let Applied::Settled(result) = slot_machine.apply(command, at_ms) {
+                log.result(&result).unwrap();
+                results.push(result);
+            }
+        }
+        results
+    }
+
+    /// 記録の操作を新しいマシンで同じ時刻に再生し、精算結果を返す
+    fn replay(recording: &Recording) -> Vec<SpinResult> {
+        let machine = recording
+            .machine
+            .as_deref()
+            .map_or_else(MachineDef::builtin, |path| MachineDef::load(path).unwrap());
+        let mut slot_machine = SlotMachine::new(machine, SessionRng::seeded(recording.seed), recording.setting);
+        let mut results = Vec::new();
+        for entry in &recording.entries {
+            if let Entry::Command { at_ms, command } = entry {
+                if let Applied::Settled(result) = slot_machine.apply(*command, *at_ms) {
+                    results.push(result);
+                }
+            }
+        }
+        results
+    }
+
+    #[test]
+    fn unfinished_logs_do_not_reveal_a_hidden_setting() {
+        let playing = "gh-slot-session 1\nseed 42\nmachine machines/lottery-3x3.toml\n@ 0 start\n@ 350 stop 1\n";
+        let recording = Recording::parse(playing).unwrap();
+        assert!(!recording.finished());
+        assert!(!recording.reveals_setting(), "遊んでいる最中のログから隠した設定が分かってしまう");
+
+        let finished = format!("{}@ 900 exit\n", playing);
+        assert!(Recording::parse(&finished).unwrap().reveals_setting());
+        // --setting で指定した設定はもともと知っているので、終了前でも明かしてよい
+        let chosen = playing.replacen("machine machines/lottery-3x3.toml\n", "machine machines/lottery-3x3.toml\nsetting 4\n", 1);
+        assert!(Recording::parse(&chosen).unwrap().reveals_setting());
+    }
+
+    #[test]
+    fn recorded_sessions_replay_to_the_same_results() {
+        let lottery = Path::new("machines/lottery-3x3.toml");
+        // 組み込みマシン、設定を指定した抽選式、設定を隠した抽選式（シードから抽選し直す）
+        let cases = [(None, None, 7), (Some(lottery), Some(5), 8), (Some(lottery), None, 9)];
+        for (machine, setting, seed) in cases {
+            let path = std::env::temp_dir().join(format!("gh-slot-test-session-{}-{}.log", seed, std::process::id()));
+            let recorded = record(&path, machine, setting, seed);
+            let recording = Recording::load(&path).unwrap();
+            let _ = fs::remove_file(&path);
+
+            assert!(recorded.len() >= 20, "精算まで進んだゲームが少なすぎる: {}", recorded.len());
+            assert_eq!(recording.seed, seed);
+            assert_eq!(recording.machine.as_deref(), machine);
+            assert_eq!(recording.setting, setting);
+            let logged: Vec<SpinResult> = recording
+                .entries
+                .iter()
+                .filter_map(|entry| match entry {
+                    Entry::Result(result) => Some(result.clone()),
+                    _ => None,
+                })
+                .collect();
+            assert_eq!(logged, recorded, "ログの精算結果が遊んだ結果と違う");
+            assert_eq!(replay(&recording), recorded, "再生の停止位置・配当が記録と違う（シード {}）", seed);
+        }
+    }
+}
@@ -0,0 +1,185 @@
+// === 設定モジュール ===
+// パチスロの「設定1〜6」：設定ごとに内部抽選テーブル（＝当選確率と払い出し率）を切り替える
+// 設定はホール側が --setting で決めるか、セッション開始時に隠して抽選する
+// プレイヤーは当選役の出現率から設定を推測し、セッション終了時に答え合わせをする
+use std::io::{self, Write};
+
+use crate::lottery::Outcome;
+use crate::machine::MachineDef;
+use crate::reel::LineWin;
+
+// 設定の段階数（設定1〜6）
+pub const SETTINGS: usize = 6;
+
+/// 設定推測のための集計
+/// 当選役ごとの出現数と、各設定のもとで観測結果が得られる尤度を記録する
+pub struct SettingStats {
+    pub games: u64,             // 集計したゲーム数
+    pub counts: Vec<u64>,       // 抽選テーブルの行ごとの出現数（ハズレを含む）
+    log_likelihood: Vec<f64>,   // 設定ごとの対数尤度
+}
+
+impl SettingStats {
+    /// 抽選テーブルの行数に合わせて空の集計を作成
+    pub fn new(entries: usize) -> Self {
+        Self {
+            games: 0,
+            counts: vec![0; entries],
+            log_likelihood: vec![0.0; SETTINGS],
+        }
+    }
+
+    /// 1ゲームの結果を記録する
+    /// 当選確率は有効ライン数に比例して下がるため、そのゲームの有効ライン数で補正する
+    /// （取りこぼしは考慮しないため、推測はあくまで目安）
+    ///
+    /// # 引数
+    /// * `machine` - マシン定義（設定ごとの抽選テーブルを参照する）
+    /// * `wins` - そのゲームの当選ライン
+    /// * `lines` - そのゲームの有効ライン数
+    pub fn record(&mut self, machine: &MachineDef, wins: &[LineWin], lines: usize) {
+        let observed = match wins.first() {
+            Some(win) => Outcome::Win {
+                symbol: win.symbol,
+                count: win.count,
+            },
+            None => Outcome::Miss,
+        };
+        let Some(index) = machine
+            .lottery(1)
+            .and_then(|lottery| lottery.entries().iter().position(|entry| entry.outcome == observed))
+        else {
+            return;  // 抽選テーブルにない結果は推測に使わない
+        };
+        self.games += 1;
+        self.counts[index] += 1;
+
+        let ratio = lines as f64 / machine.paylines.all().len() as f64;
+        for setting in 1..=SETTINGS {
+            let Some(lottery) = machine.lottery(setting) else {
+                continue;
+            };
+            let probability = match observed {
+                // ハズレ：ハズレの抽選に加え、有効ラインが少ないために外れた当選も含む
+                Outcome::Miss => lottery
+                    .entries()
+                    .iter()
+                    .map(|entry| match entry.outcome {
+                        Outcome::Miss => lottery.probability(entry),
+                        Outcome::Win { .. } => lottery.probability(entry) * (1.0 - ratio),
+                    })
+                    .sum(),
+                Outcome::Win { .. } => lottery.probability(&lottery.entries()[index]) * ratio,
+            };
+            self.log_likelihood[setting - 1] += probability.max(f64::MIN_POSITIVE).ln();
+        }
+    }
+
+    /// 観測結果から見た各設定の確からしさ（どの設定も同じ確率で選ばれると仮定）
+    ///
+    /// # 戻り値
+    /// 設定1〜6の順に、合計1になる確率
+    pub fn posterior(&self) -> Vec<f64> {
+        let best = self.log_likelihood.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        let weights: Vec<f64> = self.log_likelihood.iter().map(|value| (value - best).exp()).collect();
+        let total: f64 = weights.iter().sum();
+        weights.iter().map(|weight| weight / total).collect()
+    }
+
+    /// セッション終了時の答え合わせを出力する
+    ///
+    /// # 引数
+    /// * `machine` - マシン定義
+    /// * `setting` - 実際の設定
+    pub fn write_reveal(&self, machine: &MachineDef, setting: usize, out: &mut impl Write) -> io::Result<()> {
+        writeln!(out, "本日の設定: {}", setting)?;
+        writeln!(out, "集計ゲーム数: {}", self.games)?;
+        let Some(lottery) = machine.lottery(setting) else {
+            return Ok(());
+        };
+        for (entry, &count) in lottery.entries().iter().zip(&self.counts) {
+            if entry.outcome == Outcome::Miss {
+                continue;
+            }
+            writeln!(
+                out,
+                "  {}  出現 {:>5}回  {}",
+                machine.outcome_label(entry.outcome),
+                count,
+                frequency(self.games, count)
+            )?;
+        }
+        let posterior: Vec<String> = self
+            .posterior()
+            .iter()
+            .enumerate()
+            .map(|(i, probability)| format!("設定{} {:.1}%", i + 1, probability * 100.0))
+            .collect();
+        writeln!(out, "出現率から見た各設定の可能性: {}", posterior.join(" / "))?;
+        Ok(())
+    }
+}
+
+/// 出現間隔の表示（「1/12.3」、未出現なら「-」）
+pub fn frequency(games: u64, count: u64) -> String {
+    if count == 0 {
+        "-".to_string()
+    } else {
+        format!("1/{:.1}", games as f64 / count as f64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::SessionRng;
+    use std::path::Path;
+
+    /// 設定1〜6の抽選テーブルを持つマシン（7ライン）
+    fn lottery_machine() -> MachineDef {
+        MachineDef::from_toml(
+            include_str!("../machines/lottery-3x3.toml"),
+            Path::new("machines/lottery-3x3.toml"),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn no_games_leave_every_setting_equally_likely() {
+        let machine = lottery_machine();
+        let stats = SettingStats::new(machine.lottery(1).unwrap().entries().len());
+        for probability in stats.posterior() {
+            assert!((probability - 1.0 / SETTINGS as f64).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn games_drawn_from_setting_6_rank_it_highest() {
+        let machine = lottery_machine();
+        let lottery = machine.lottery(6).unwrap();
+        let lines = machine.max_lines();
+        let mut stats = SettingStats::new(lottery.entries().len());
+        let mut rng = SessionRng::seeded(6);
+        // 全ライン有効で設定6の抽選テーブルから引いた結果を、そのまま揃ったものとして記録する
+        for _ in 0..50_000 {
+            let wins = match lottery.draw(&mut rng, lines, lines) {
+                Outcome::Miss => vec![],
+                Outcome::Win { symbol, count } => vec![LineWin {
+                    line: Some(0),
+                    entry: 0,
+                    symbol,
+                    count,
+                    payout: 0,
+                    multiplier: 1,
+                    ways: 1,
+                }],
+            };
+            stats.record(&machine, &wins, lines);
+        }
+        assert_eq!(stats.games, 50_000);
+        let posterior = stats.posterior();
+        assert!((posterior.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+        let best = (0..SETTINGS).max_by(|&a, &b| posterior[a].total_cmp(&posterior[b])).unwrap();
+        assert_eq!(best + 1, 6, "{:?}", posterior);
+    }
+}
@@ -0,0 +1,401 @@
+// === ヘッドレスシミュレーションモジュール ===
+// 画面もタイマーも使わずに大量の回転を行い、マシンの数値（RTP・当選頻度・分散）を集計する
+// 停止位置の決め方と当選判定はゲーム本体と同じ処理（visible_symbols / evaluate_all / SlipControl::choose_stop）を使う
+use rand::Rng;
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+
+use crate::bonus::{GameState, Transition};
+use crate::machine::{MachineDef, SymbolId};
+use crate::cascade;
+use crate::reel::{visible_symbols, Evaluation};
+use crate::scatter::{self, FreeSpinEvent, FreeSpins};
+
+// 信頼区間の係数（95%信頼区間）
+const Z_95: f64 = 1.96;
+
+/// シミュレーションの設定
+pub struct SimulationConfig {
+    pub spins: u64,    // 回転数
+    pub lines: usize,  // 有効ライン数（1ラインあたり1枚掛けで計算）
+    pub setting: usize, // 設定（1〜6、設定のないマシンでは無視される）
+}
+
+/// 集計の1項目（当選回数と配当合計）
+#[derive(Default, Clone, Copy)]
+pub struct WinStat {
+    pub hits: u64,
+    pub payout: u64,
+}
+
+/// ボーナスごとの集計
+#[derive(Default, Clone, Copy)]
+pub struct BonusStat {
+    pub triggers: u64,  // 突入回数
+    pub games: u64,     // ボーナス中に消化したゲーム数
+    pub payout: u64,    // ボーナス中の配当合計
+}
+
+/// フリースピンの集計
+#[derive(Default, Clone, Copy)]
+pub struct FreeSpinStat {
+    pub triggers: u64,     // 突入回数（リトリガーは含まない）
+    pub retriggers: u64,   // フリースピン中の回数追加
+    pub spins: u64,        // 消化したフリースピンの回数
+    pub payout: u64,       // フリースピン中の配当合計
+}
+
+/// シミュレーション結果
+pub struct SimulationReport {
+    pub spins: u64,
+    pub lines: usize,
+    pub setting: usize,
+    pub total_bet: u64,                              // ベット合計
+    pub total_win: u64,                              // 配当合計
+    pub hits: u64,                                   // 1ライン以上当選した回転数
+    pub max_win: u64,                                // 1回転あたりの最大配当
+    pub lines_stats: Vec<WinStat>,                   // ラインごとの集計
+    pub pay_stats: BTreeMap<usize, WinStat>,         // 配当表の行（Paytable::entries の添字）ごとの集計
+    pub bonus_stats: Vec<BonusStat>,                 // ボーナスごとの集計（MachineDef::bonuses の順）
+    pub free_spin_stat: FreeSpinStat,                // フリースピンの集計
+    sum_return: f64,                                 // 1回転の払い戻し率（配当/ベット）の合計
+    sum_return_sq: f64,                              // 同・二乗の合計（分散の計算用）
+}
+
+impl SimulationReport {
+    /// 1回転あたりのベット額
+    pub fn bet_per_spin(&self) -> u64 {
+        self.lines as u64
+    }
+
+    /// RTP（ベットに対する配当の割合）
+    pub fn rtp(&self) -> f64 {
+        self.total_win as f64 / self.total_bet as f64
+    }
+
+    /// 当選頻度（1ライン以上当選した回転の割合）
+    pub fn hit_frequency(&self) -> f64 {
+        self.hits as f64 / self.spins as f64
+    }
+
+    /// ボラティリティ：1回転の払い戻し率の標準偏差（ベット1単位あたり）
+    pub fn std_dev(&self) -> f64 {
+        let n = self.spins as f64;
+        if self.spins < 2 {
+            return 0.0;
+        }
+        let mean = self.sum_return / n;
+        let variance = (self.sum_return_sq - n * mean * mean) / (n - 1.0);
+        variance.max(0.0).sqrt()
+    }
+
+    /// RTPの95%信頼区間の半幅
+    pub fn rtp_margin(&self) -> f64 {
+        Z_95 * self.std_dev() / (self.spins as f64).sqrt()
+    }
+
+    /// 当選頻度の95%信頼区間の半幅（正規近似）
+    pub fn hit_frequency_margin(&self) -> f64 {
+        let p = self.hit_frequency();
+        Z_95 * (p * (1.0 - p) / self.spins as f64).sqrt()
+    }
+
+    /// 集計結果を表形式で出力する
+    pub fn write_to(&self, machine: &MachineDef, out: &mut impl Write) -> io::Result<()> {
+        let percent = |value: f64| value * 100.0;
+        // 出現間隔（一度も出ていなければ割り算をせず「-」）
+        let cycle = |spins: u64, count: u64| {
+            if count == 0 {
+                "-".to_string()
+            } else {
+                format!("1/{:.1}", spins as f64 / count as f64)
+            }
+        };
+        writeln!(out, "🎰 シミュレーション結果: {}", machine.name)?;
+        if machine.free_spins.is_some() {
+            writeln!(out, "回転数        : {}（うちフリースピン {}）", self.spins, self.free_spin_stat.spins)?;
+        } else {
+            writeln!(out, "回転数        : {}", self.spins)?;
+        }
+        if machine.evaluation == Evaluation::Ways {
+            writeln!(out, "判定方式      : {}ウェイ（1回転 {} クレジット）", machine.total_ways(), self.bet_per_spin())?;
+        } else {
+            writeln!(out, "有効ライン数  : {}（1回転 {} クレジット）", self.lines, self.bet_per_spin())?;
+        }
+        if let Some(cascade) = &machine.cascade {
+            writeln!(out, "連鎖倍率      : {}", cascade.label())?;
+        }
+        if machine.has_settings() {
+            writeln!(out, "設定          : {}", self.setting)?;
+        }
+        if machine.has_lottery() {
+            writeln!(
+                out,
+                "停止制御      : 内部抽選（抽選結果に合わせて最大{}コマすべらせる）",
+                machine.slip.max_slip()
+            )?;
+        }
+        // プレイヤーの操作や他のセッションで結果が変わる機能は回さない
+        let excluded: Vec<&str> = [
+            (machine.hold.is_some(), "ホールド"),
+            (machine.nudge.is_some(), "ナッジ"),
+            (machine.gamble.is_some(), "ダブルアップ"),
+            (machine.jackpot.is_some(), "ジャックポットの積立"),
+        ]
+        .into_iter()
+        .filter_map(|(has, name)| has.then_some(name))
+        .collect();
+        if !excluded.is_empty() {
+            writeln!(out, "対象外        : {}（RTPに含まない）", excluded.join("・"))?;
+        }
+        writeln!(out, "ベット合計    : {}", self.total_bet)?;
+        writeln!(out, "配当合計      : {}", self.total_win)?;
+        writeln!(
+            out,
+            "RTP           : {:.3}% ± {:.3}%（95%信頼区間）",
+            percent(self.rtp()),
+            percent(self.rtp_margin())
+        )?;
+        writeln!(
+            out,
+            "当選頻度      : {:.3}% ± {:.3}%（95%信頼区間、1/{:.2}）",
+            percent(self.hit_frequency()),
+            percent(self.hit_frequency_margin()),
+            1.0 / self.hit_frequency().max(f64::MIN_POSITIVE)
+        )?;
+        writeln!(
+            out,
+            "最大配当      : {}（ベットの{:.1}倍）",
+            self.max_win,
+            self.max_win as f64 / self.bet_per_spin() as f64
+        )?;
+        writeln!(out, "標準偏差      : {:.3}（ベット1単位あたり）", self.std_dev())?;
+
+        // ウェイ判定のマシンにはラインがない
+        if !self.lines_stats.is_empty() {
+            writeln!(out)?;
+            writeln!(out, "ライン別:")?;
+            for (i, stat) in self.lines_stats.iter().enumerate() {
+                let rows: Vec<String> = machine.paylines.all()[i]
+                    .iter()
+                    .map(|row| (row + 1).to_string())
+                    .collect();
+                writeln!(
+                    out,
+                    "  ライン{:<2} [{}]  当選 {:>10}  配当 {:>12}  RTP寄与 {:>8.3}%",
+                    i + 1,
+                    rows.join(", "),
+                    stat.hits,
+                    stat.payout,
+                    percent(stat.payout as f64 / self.total_bet as f64)
+                )?;
+            }
+        }
+
+        writeln!(out)?;
+        writeln!(out, "役別:")?;
+        for (&entry, stat) in &self.pay_stats {
+            writeln!(
+                out,
+                "  {}  当選 {:>10}  頻度 {:<14}  配当 {:>12}  RTP寄与 {:>8.3}%",
+                machine.pay_label(&machine.paytable.entries()[entry]),
+                stat.hits,
+                cycle(self.spins, stat.hits),
+                stat.payout,
+                percent(stat.payout as f64 / self.total_bet as f64)
+            )?;
+        }
+
+        if let Some(free_spins) = &machine.free_spins {
+            let stat = &self.free_spin_stat;
+            let per_trigger = |value: u64| value as f64 / stat.triggers.max(1) as f64;
+            writeln!(out)?;
+            writeln!(out, "フリースピン:")?;
+            writeln!(
+                out,
+                "  {} {}個以上  突入 {:>8}  頻度 {:<12}  追加 {:>8}  平均 {:.1}回・獲得 {:.1}  RTP寄与 {:>8.3}%",
+                machine.glyph(free_spins.symbol),
+                free_spins.count,
+                stat.triggers,
+                cycle(self.spins - stat.spins, stat.triggers),
+                stat.retriggers,
+                per_trigger(stat.spins),
+                per_trigger(stat.payout),
+                percent(stat.payout as f64 / self.total_bet as f64)
+            )?;
+        }
+
+        if !machine.bonuses.is_empty() {
+            writeln!(out)?;
+            writeln!(out, "ボーナス:")?;
+            for (bonus, stat) in machine.bonuses.iter().zip(&self.bonus_stats) {
+                let per_trigger = |value: u64| value as f64 / stat.triggers.max(1) as f64;
+                writeln!(
+                    out,
+                    "  {} {} ×{}  突入 {:>8}  頻度 {:<12}  平均 {:.1}ゲーム・獲得 {:.1}  RTP寄与 {:>8.3}%",
+                    bonus.kind,
+                    machine.glyph(bonus.symbol),
+                    bonus.count,
+                    stat.triggers,
+                    cycle(self.spins, stat.triggers),
+                    per_trigger(stat.games),
+                    per_trigger(stat.payout),
+                    percent(stat.payout as f64 / self.total_bet as f64)
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// シミュレーションを実行する
+///
+/// # 引数
+/// * `machine` - 対象のマシン定義
+/// * `config` - 回転数・有効ライン数
+/// * `rng` - 停止位置を決める乱数生成器
+pub fn run(machine: &MachineDef, config: &SimulationConfig, rng: &mut impl Rng) -> SimulationReport {
+    let paylines = machine.paylines.active(config.lines);
+    // ウェイ判定はペイラインを使わず、1ライン分のベットで回す
+    let lines = if machine.evaluation == Evaluation::Ways { 1 } else { paylines.len() };
+    let bet = lines as u64;  // 1ラインあたり1枚掛け
+    let mut report = SimulationReport {
+        spins: config.spins,
+        lines,
+        setting: config.setting,
+        total_bet: 0,
+        total_win: 0,
+        hits: 0,
+        max_win: 0,
+        lines_stats: vec![WinStat::default(); paylines.len()],
+        pay_stats: BTreeMap::new(),
+        bonus_stats: vec![BonusStat::default(); machine.bonuses.len()],
+        free_spin_stat: FreeSpinStat::default(),
+        sum_return: 0.0,
+        sum_return_sq: 0.0,
+    };
+
+    let mut window: Vec<Vec<SymbolId>> = vec![Vec::new(); machine.reels.len()];
+    let mut stops: Vec<Option<usize>> = vec![None; machine.reels.len()];
+    let mut state = GameState::Normal;
+    let mut free_spins: Option<FreeSpins> = None;
+    for _ in 0..config.spins {
+        // 抽選モードではゲーム本体と同じく回転開始時に結果を決める（ボーナス中はボーナスの抽選テーブル）
+        let outcome = state
+            .lottery(machine, config.setting)
+            .map(|lottery| lottery.draw(rng, paylines.len(), machine.paylines.all().len()));
+        stops.fill(None);
+
+        // 各リールの停止ボタンを押す位置をランダムに決める（押し順・タイミングによる停止と同じく一様）
+        for (reel, strip) in machine.reels.iter().enumerate() {
+            let pressed = rng.gen_range(0..strip.len());
+            let stop = match outcome {
+                Some(outcome) => machine.slip.choose_stop(machine, paylines, outcome, &stops, reel, pressed),
+                None => pressed,
+            };
+            stops[reel] = Some(stop);
+            window[reel] = visible_symbols(strip, stop, machine.rows);
+        }
+
+        // カスケードのマシンは連鎖した分の当選も含める
+        let positions: Vec<usize> = stops.iter().flatten().copied().collect();
+        let wins = cascade::evaluate_all(machine, &window, &positions, paylines, 1);
+        let scatters = scatter::evaluate_scatters(&window, machine, bet);
+        let win: u64 = wins.iter().map(|win| win.payout).sum::<u64>()
+            + scatters.iter().map(|win| win.payout).sum::<u64>();
+        // フリースピンはベットなしで回す
+        let free = free_spins.is_some();
+        if free {
+            report.free_spin_stat.spins += 1;
+            report.free_spin_stat.payout += win;
+        }
+        if let Some(def) = &machine.free_spins {
+            match scatter::advance(&mut free_spins, def, &window, win) {
+                Some(FreeSpinEvent::Awarded(_)) => report.free_spin_stat.triggers += 1,
+                Some(FreeSpinEvent::Retriggered(_)) => report.free_spin_stat.retriggers += 1,
+                _ => {}
+            }
+        }
+        if let Some(run) = state.run() {
+            let bonus = &mut report.bonus_stats[run.bonus];
+            bonus.games += 1;
+            bonus.payout += win;
+        }
+        if let (Some(Transition::Started(_)), Some(run)) = (state.advance(machine, &wins, 1), state.run()) {
+            report.bonus_stats[run.bonus].triggers += 1;
+        }
+        for line_win in &wins {
+            if let Some(line) = line_win.line {
+                let line = &mut report.lines_stats[line];
+                line.hits += 1;
+                line.payout += line_win.payout;
+            }
+            let pay = report.pay_stats.entry(line_win.entry).or_default();
+            pay.hits += 1;
+            pay.payout += line_win.payout;
+        }
+        for scatter_win in &scatters {
+            let pay = report.pay_stats.entry(scatter_win.entry).or_default();
+            pay.hits += 1;
+            pay.payout += scatter_win.payout;
+        }
+
+        if !free {
+            report.total_bet += bet;
+        }
+        report.total_win += win;
+        if win > 0 {
+            report.hits += 1;
+        }
+        report.max_win = report.max_win.max(win);
+        let ratio = win as f64 / bet as f64;
+        report.sum_return += ratio;
+        report.sum_return_sq += ratio * ratio;
+    }
+
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsheet;
+    use crate::rng::SessionRng;
+    use std::path::Path;
+
+    #[test]
+    fn simulated_rtp_matches_the_par_sheet() {
+        // 組み込みマシンの7ラインの理論RTPは 49,525 ÷ (9,261 × 7) = 76.3956%
+        let machine = MachineDef::builtin();
+        let exact = parsheet::compute(&machine, 7).rtp();
+        assert!((exact - 49_525.0 / (9_261.0 * 7.0)).abs() < 1e-12);
+        let config = SimulationConfig { spins: 200_000, lines: 7, setting: 1 };
+        let report = run(&machine, &config, &mut SessionRng::seeded(76));
+        assert_eq!(report.total_bet, 200_000 * 7);
+        assert!(
+            (report.rtp() - exact).abs() < report.rtp_margin(),
+            "RTP {:.4}% ± {:.4}% / 理論値 {:.4}%",
+            report.rtp() * 100.0,
+            report.rtp_margin() * 100.0,
+            exact * 100.0
+        );
+    }
+
+    #[test]
+    fn reports_name_the_features_left_out_of_the_rtp() {
+        let config = SimulationConfig { spins: 100, lines: 1, setting: 1 };
+        let report_of = |machine: &MachineDef| {
+            let mut out = Vec::new();
+            run(machine, &config, &mut SessionRng::seeded(1)).write_to(machine, &mut out).unwrap();
+            String::from_utf8(out).unwrap()
+        };
+        let classic = MachineDef::from_toml(
+            include_str!("../machines/classic-3x1.toml"),
+            Path::new("machines/classic-3x1.toml"),
+        )
+        .unwrap();
+        assert!(report_of(&classic)
+            .contains("対象外        : ホールド・ナッジ・ダブルアップ・ジャックポットの積立（RTPに含まない）"));
+        assert!(!report_of(&MachineDef::builtin()).contains("対象外"));
+    }
+}
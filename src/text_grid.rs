@@ -0,0 +1,125 @@
+// === 文字の格子バックエンド ===
+// 画面をターミナルに出さず、メモリ上の文字の格子に書き込む Renderer の実装
+// 描いた結果を文字列として取り出せるため、画面の出力をそのまま比較するテストに使う
+// 色は記録しない（比べるのは文字と配置だけ）
+use std::io;
+
+use crate::render::{Clear, Color, Renderer};
+
+/// メモリ上の画面
+/// 全角文字・絵文字は2桁を使う（2桁目は空のマスにして、文字列にするときは飛ばす）
+#[derive(Default)]
+pub struct TextGrid {
+    rows: Vec<Vec<String>>,  // 行ごとのマス（1マスに1文字と、続く結合文字）
+    x: usize,                // カーソルの桁
+    y: usize,                // カーソルの行
+}
+
+impl TextGrid {
+    /// 空の画面を作る
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 画面の内容（行末の空白と、最後の空行は除く）
+    pub fn text(&self) -> String {
+        let mut lines: Vec<String> = self
+            .rows
+            .iter()
+            .map(|row| row.concat().trim_end().to_string())
+            .collect();
+        while lines.last().is_some_and(|line| line.is_empty()) {
+            lines.pop();
+        }
+        lines.join("\n")
+    }
+
+    /// カーソルのある行（なければ空のマスで作る）
+    fn row(&mut self, y: usize) -> &mut Vec<String> {
+        if self.rows.len() <= y {
+            self.rows.resize(y + 1, Vec::new());
+        }
+        &mut self.rows[y]
+    }
+
+    /// 1マスに書く（全角文字の片側を上書きしたら、残った側は空白にする）
+    fn put(&mut self, x: usize, cell: String) {
+        let row = self.row(self.y);
+        if row.len() <= x {
+            row.resize(x + 1, " ".to_string());
+        }
+        if row[x].is_empty() && x > 0 {
+            row[x - 1] = " ".to_string();
+        }
+        if row.get(x + 1).is_some_and(|next| next.is_empty()) {
+            row[x + 1] = " ".to_string();
+        }
+        row[x] = cell;
+    }
+}
+
+impl Renderer for TextGrid {
+    fn move_to(&mut self, x: u16, y: u16) -> io::Result<()> {
+        self.x = x as usize;
+        self.y = y as usize;
+        Ok(())
+    }
+
+    fn set_color(&mut self, _color: Color) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn print(&mut self, text: &str) -> io::Result<()> {
+        for c in text.chars() {
+            match width(c) {
+                // 結合文字（異体字セレクタなど）は直前の文字と同じマスに入れる
+                0 => {
+                    let (x, y) = (self.x, self.y);
+                    let row = self.row(y);
+                    let end = x.min(row.len());
+                    if let Some(cell) = row[..end].iter_mut().rev().find(|cell| !cell.is_empty()) {
+                        cell.push(c);
+                    }
+                }
+                1 => {
+                    self.put(self.x, c.to_string());
+                    self.x += 1;
+                }
+                _ => {
+                    self.put(self.x, c.to_string());
+                    self.put(self.x + 1, String::new());
+                    self.x += 2;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn clear(&mut self, clear: Clear) -> io::Result<()> {
+        match clear {
+            Clear::All => self.rows.clear(),
+            Clear::CurrentLine => self.row(self.y).clear(),
+            Clear::FromCursorDown => {
+                let (x, y) = (self.x, self.y);
+                self.rows.truncate(y + 1);
+                let row = self.row(y);
+                if row.get(x).is_some_and(|cell| cell.is_empty()) && x > 0 {
+                    row[x - 1] = " ".to_string();
+                }
+                row.truncate(x);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 画面上の文字の幅（桁数）
+/// 半角英数字と罫線は1桁、結合文字は0桁、それ以外（全角文字・絵文字）は2桁とする
+fn width(c: char) -> usize {
+    match c {
+        '\u{0300}'..='\u{036F}' | '\u{200D}' | '\u{20E3}' | '\u{FE00}'..='\u{FE0F}' => 0,
+        _ if c.is_ascii() => 1,
+        '\u{2500}'..='\u{257F}' => 1,
+        _ => 2,
+    }
+}
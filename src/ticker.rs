@@ -0,0 +1,82 @@
+// === ゲームループの時計モジュール ===
+// 描画ループを一定の間隔（ティック）で進め、すべてのリールの表示をこの1つの時計で更新する
+// リールごとにスレッドやタスクを起こさないため、同じリールを2重に回すことがない
+// 表示位置はセッション開始からの経過時間で決まるので、ティックが遅れても回転速度は変わらない
+use std::time::Duration;
+
+use crate::clock::Clock;
+
+/// ゲームループの1ティック（操作の受け付け・リール表示の更新・描画の間隔）
+pub const FRAME: Duration = Duration::from_millis(35);
+
+/// 一定間隔でティックを刻むゲームループの時計
+/// 時刻と待ち時間は差し込んだ時計（実時間 / テスト用の手で進める時計）から得る
+pub struct Ticker<C: Clock> {
+    clock: C,        // 時刻の取得と待機に使う時計
+    period_ms: u64,  // ティックの間隔（ミリ秒）
+    next: u64,       // 次のティックの番号（セッション開始から period_ms × next の時点）
+}
+
+impl<C: Clock> Ticker<C> {
+    /// 時計とティックの間隔から作る
+    ///
+    /// # 引数
+    /// * `clock` - 時刻の取得と待機に使う時計
+    /// * `period` - ティックの間隔（1ミリ秒未満は1ミリ秒）
+    pub fn new(clock: C, period: Duration) -> Self {
+        Self {
+            clock,
+            period_ms: (period.as_millis() as u64).max(1),
+            next: 1,
+        }
+    }
+
+    /// 現在の時刻（セッション開始からのミリ秒）
+    pub fn now_ms(&self) -> u64 {
+        self.clock.now_ms()
+    }
+
+    /// 差し込んだ時計（テストで時刻を進めるときに使う）
+    pub fn clock(&self) -> &C {
+        &self.clock
+    }
+
+    /// 次のティックまで待つ
+    /// ティックの予定時刻は開始時刻からの倍数で決めるため、処理に時間がかかっても間隔がずれていかない
+    /// 予定時刻をすでに過ぎたティックは待たずに飛ばす（遅れを取り戻そうと連続で描画しない）
+    ///
+    /// # 戻り値
+    /// 待ち終えた時刻（セッション開始からのミリ秒）
+    pub fn wait(&mut self) -> u64 {
+        let passed = self.clock.now_ms() / self.period_ms;
+        self.next = self.next.max(passed + 1);
+        self.clock.sleep_until(self.next * self.period_ms);
+        self.next += 1;
+        self.clock.now_ms()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::ManualClock;
+
+    #[test]
+    fn ticks_land_on_multiples_of_the_period() {
+        let mut ticker = Ticker::new(ManualClock::new(), Duration::from_millis(35));
+        let times: Vec<u64> = (0..5).map(|_| ticker.wait()).collect();
+        assert_eq!(times, vec![35, 70, 105, 140, 175]);
+    }
+
+    #[test]
+    fn late_ticks_are_skipped_without_drifting() {
+        let mut ticker = Ticker::new(ManualClock::new(), Duration::from_millis(35));
+        assert_eq!(ticker.wait(), 35);
+        // 1ティックの処理に100ミリ秒かかった（70・105 の予定は過ぎている）
+        ticker.clock().advance(100);
+        assert_eq!(ticker.wait(), 140);
+        // 予定より少し早く戻っても次は次の倍数まで待つ
+        ticker.clock().advance(10);
+        assert_eq!(ticker.wait(), 175);
+    }
+}
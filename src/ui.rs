@@ -0,0 +1,51 @@
+// === ターミナル画面モジュール ===
+// ライブラリの画面描画（Renderer）を crossterm でターミナルに出力するバックエンド
+// 画面の配置はライブラリが決め、ここでは移動・色・文字・消去をターミナルのコマンドに置き換えるだけ
+use std::io::{self, stdout};
+
+use crossterm::{
+    cursor,                                                    // カーソル移動機能
+    execute,                                                   // ターミナルコマンド実行マクロ
+    style::{self, Print, SetForegroundColor},                 // 色設定と文字出力
+    terminal::{self, ClearType},                              // ターミナル制御（画面クリアなど）
+};
+use gh_slot::render::{Clear, Color, Renderer, NUDGE_KEYS};
+
+/// 標準出力のターミナル
+pub struct Terminal;
+
+impl Renderer for Terminal {
+    fn move_to(&mut self, x: u16, y: u16) -> io::Result<()> {
+        execute!(stdout(), cursor::MoveTo(x, y))
+    }
+
+    fn set_color(&mut self, color: Color) -> io::Result<()> {
+        let color = match color {
+            Color::White => style::Color::White,
+            Color::Red => style::Color::Red,
+            Color::Yellow => style::Color::Yellow,
+            Color::Green => style::Color::Green,
+            Color::Cyan => style::Color::Cyan,
+            Color::Magenta => style::Color::Magenta,
+        };
+        execute!(stdout(), SetForegroundColor(color))
+    }
+
+    fn print(&mut self, text: &str) -> io::Result<()> {
+        execute!(stdout(), Print(text))
+    }
+
+    fn clear(&mut self, clear: Clear) -> io::Result<()> {
+        let clear = match clear {
+            Clear::All => ClearType::All,
+            Clear::CurrentLine => ClearType::CurrentLine,
+            Clear::FromCursorDown => ClearType::FromCursorDown,
+        };
+        execute!(stdout(), terminal::Clear(clear))
+    }
+}
+
+/// キーがナッジキーならリールの番号を返す（大文字・小文字は問わない）
+pub fn nudge_key(c: char, reels: usize) -> Option<usize> {
+    NUDGE_KEYS[..reels].iter().position(|&key| key == c.to_ascii_lowercase())
+}
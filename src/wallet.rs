@@ -0,0 +1,213 @@
+// === クレジット（持ちコイン）管理モジュール ===
+// プレイヤーの所持クレジット、1ゲームあたりのベット額、配当の受け取りを管理する
+use std::fmt;
+
+// === ウォレットの基本設定 ===
+pub const INITIAL_CREDITS: u64 = 100;     // ゲーム開始時の所持クレジット
+pub const MAX_COINS_PER_LINE: u64 = 3;    // 1ラインあたりに掛けられる最大枚数
+
+/// ベットできなかった理由
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BetError {
+    /// 所持クレジットがベット額に足りない
+    InsufficientCredits { balance: u64, bet: u64 },
+}
+
+impl fmt::Display for BetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BetError::InsufficientCredits { balance, bet } => write!(
+                f,
+                "クレジット不足です（所持: {} / 必要: {}）",
+                balance, bet
+            ),
+        }
+    }
+}
+
+/// プレイヤーのクレジット残高とベット設定
+pub struct Wallet {
+    balance: u64,          // 現在の所持クレジット
+    coins_per_line: u64,   // 1ラインあたりの掛け枚数（1〜MAX_COINS_PER_LINE）
+    lines: usize,          // 掛けているライン数（有効ライン数）
+    last_win: u64,         // 直前のゲームで獲得したクレジット
+}
+
+impl Wallet {
+    /// 新しいウォレットを作成
+    ///
+    /// # 引数
+    /// * `balance` - 開始時の所持クレジット
+    /// * `lines` - 掛けるライン数
+    pub fn new(balance: u64, lines: usize) -> Self {
+        Self {
+            balance,
+            coins_per_line: 1,  // 初期状態は1枚掛け
+            lines,
+            last_win: 0,
+        }
+    }
+
+    /// 現在の所持クレジット
+    pub fn balance(&self) -> u64 {
+        self.balance
+    }
+
+    /// 1ラインあたりの掛け枚数
+    pub fn coins_per_line(&self) -> u64 {
+        self.coins_per_line
+    }
+
+    /// 掛けているライン数
+    pub fn lines(&self) -> usize {
+        self.lines
+    }
+
+    /// 直前のゲームで獲得したクレジット
+    pub fn last_win(&self) -> u64 {
+        self.last_win
+    }
+
+    /// 1ゲームあたりのベット額（掛け枚数 × ライン数）
+    pub fn bet(&self) -> u64 {
+        self.coins_per_line * self.lines as u64
+    }
+
+    /// 1ラインあたりの掛け枚数を1→2→…→MAX_COINS_PER_LINE→1の順に切り替える
+    pub fn cycle_coins_per_line(&mut self) {
+        self.coins_per_line = self.coins_per_line % MAX_COINS_PER_LINE + 1;
+    }
+
+    /// 掛けるライン数を選択肢の中で次の値に切り替える（最大の次は最小に戻る）
+    ///
+    /// # 引数
+    /// * `options` - 選択できるライン数（昇順）
+    pub fn cycle_lines(&mut self, options: &[usize]) {
+        self.lines = options
+            .iter()
+            .copied()
+            .find(|&lines| lines > self.lines)
+            .or_else(|| options.first().copied())
+            .unwrap_or(self.lines);
+    }
+
+    /// ゲーム開始時にベット額を残高から差し引く
+    ///
+    /// # 戻り値
+    /// 成功時は差し引いたベット額、残高不足の場合はBetError
+    pub fn place_bet(&mut self) -> Result<u64, BetError> {
+        let bet = self.bet();
+        if self.balance < bet {
+            return Err(BetError::InsufficientCredits {
+                balance: self.balance,
+                bet,
+            });
+        }
+        self.balance -= bet;
+        self.last_win = 0;  // 新しいゲームが始まったので前回の獲得額をリセット
+        Ok(bet)
+    }
+
+    /// リール停止後の配当を残高に加算する
+    pub fn credit(&mut self, amount: u64) {
+        self.balance += amount;
+        self.last_win = amount;
+    }
+
+    /// フリースピンのゲームを始める（ベット額は差し引かない）
+    pub fn start_free_spin(&mut self) {
+        self.last_win = 0;
+    }
+
+    /// フリースピン中の配当を直前の獲得額として記録する
+    /// 残高にはフリースピン終了時に deposit でまとめて加算する
+    pub fn record_free_spin_win(&mut self, amount: u64) {
+        self.last_win = amount;
+    }
+
+    /// ダブルアップの結果で直前の獲得額を差し替える
+    /// 賭けたクレジット（残高に加算済み）を戻し、結果のクレジットを加える
+    pub fn replace_win(&mut self, stake: u64, result: u64) {
+        self.balance = self.balance - stake + result;
+        self.last_win = self.last_win - stake + result;
+    }
+
+    /// フリースピンの獲得合計を残高に加算する（直前の獲得額は変えない）
+    pub fn deposit(&mut self, amount: u64) {
+        self.balance += amount;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn place_bet_debits_coins_per_line_times_lines() {
+        let mut wallet = Wallet::new(INITIAL_CREDITS, 5);
+        wallet.credit(8);
+        // (1ラインあたりの掛け枚数, 期待するベット額)
+        for (coins_per_line, bet) in [(2, 10), (3, 15), (1, 5)] {
+            wallet.cycle_coins_per_line();
+            assert_eq!(wallet.coins_per_line(), coins_per_line);
+            let balance = wallet.balance();
+            assert_eq!(wallet.place_bet(), Ok(bet));
+            assert_eq!(wallet.balance(), balance - bet);
+            // 新しいゲームでは直前の獲得額をリセットする
+            assert_eq!(wallet.last_win(), 0);
+            wallet.credit(4);
+            assert_eq!((wallet.balance(), wallet.last_win()), (balance - bet + 4, 4));
+        }
+    }
+
+    #[test]
+    fn a_bet_above_the_balance_is_refused_without_touching_it() {
+        let mut wallet = Wallet::new(8, 7);
+        assert_eq!(wallet.place_bet(), Ok(7));
+        wallet.credit(2);
+        // 残り3クレジットでは7クレジットのベットはできず、残高も直前の獲得額もそのまま
+        let error = wallet.place_bet().unwrap_err();
+        assert_eq!(error, BetError::InsufficientCredits { balance: 3, bet: 7 });
+        assert_eq!((wallet.balance(), wallet.last_win()), (3, 2));
+        assert_eq!(error.to_string(), "クレジット不足です（所持: 3 / 必要: 7）");
+        // ちょうど足りればベットできる
+        wallet.deposit(4);
+        assert_eq!(wallet.place_bet(), Ok(7));
+        assert_eq!(wallet.balance(), 0);
+    }
+
+    #[test]
+    fn cycle_lines_walks_the_options_and_wraps() {
+        let mut wallet = Wallet::new(INITIAL_CREDITS, 1);
+        let mut seen = Vec::new();
+        for _ in 0..5 {
+            wallet.cycle_lines(&[1, 3, 5, 7]);
+            seen.push((wallet.lines(), wallet.bet()));
+        }
+        assert_eq!(seen, [(3, 3), (5, 5), (7, 7), (1, 1), (3, 3)]);
+        // 選択肢にないライン数からは、次に大きい選択肢に進む
+        let mut wallet = Wallet::new(INITIAL_CREDITS, 4);
+        wallet.cycle_lines(&[1, 3, 5, 7]);
+        assert_eq!(wallet.lines(), 5);
+        // 選択肢が1つなら変わらない
+        wallet.cycle_lines(&[5]);
+        assert_eq!(wallet.lines(), 5);
+    }
+
+    #[test]
+    fn replace_win_swaps_the_stake_for_the_gamble_result() {
+        // (賭けたクレジット, ダブルアップの結果, 期待する残高, 期待する直前の獲得額)
+        let cases = [
+            (30, 60, 160, 60),  // 当たり: 賭け金が倍になる
+            (30, 0, 100, 0),    // 外れ: 賭け金は没収
+        ];
+        for (stake, result, balance, last_win) in cases {
+            // 110クレジットから10ベットして30当たった後（残高130）
+            let mut wallet = Wallet::new(110, 10);
+            wallet.place_bet().unwrap();
+            wallet.credit(30);
+            wallet.replace_win(stake, result);
+            assert_eq!((wallet.balance(), wallet.last_win()), (balance, last_win));
+        }
+    }
+}